@@ -0,0 +1,115 @@
+use crate::{
+    entities::{Board, Cell, GameResult, Outcome, Player},
+    tictactoe::TicTacToe,
+};
+
+/// board editor of the analysis screen, every edit re-evaluates the position
+#[derive(Debug)]
+pub struct Analysis {
+    pub board: Board,
+    /// side to move
+    pub player: Player,
+    pub selected_index: usize,
+    pub result: GameResult,
+    /// value of the position for the side to move
+    pub outcome: Option<Outcome>,
+    pub move_outcomes: Vec<(usize, Outcome)>,
+    /// position string being typed to import
+    pub input: Option<String>,
+    /// exported position string, import error or why the position can't be reached
+    pub message: Option<String>,
+}
+
+impl Analysis {
+    pub fn new(engine: &TicTacToe, board: Board, player: Player, selected_index: usize) -> Self {
+        let mut analysis = Analysis {
+            board,
+            player,
+            selected_index,
+            result: GameResult::Playing,
+            outcome: None,
+            move_outcomes: vec![],
+            input: None,
+            message: None,
+        };
+        analysis.evaluate(engine);
+        analysis
+    }
+
+    pub fn set_cell(&mut self, engine: &TicTacToe, cell: Cell) {
+        self.board[self.selected_index] = cell;
+        self.message = None;
+        self.evaluate(engine);
+    }
+
+    /// empty -> X -> O -> empty, through every player in turn order
+    pub fn cycle_cell(&mut self, engine: &TicTacToe) {
+        let cell = match &self.board[self.selected_index] {
+            Cell::Empty => Cell::Mark(Player::ALL[0].clone()),
+            Cell::Mark(player) if player.get_index() + 1 < Player::COUNT => {
+                Cell::Mark(player.get_next())
+            }
+            Cell::Mark(_) => Cell::Empty,
+        };
+        self.set_cell(engine, cell);
+    }
+
+    pub fn toggle_player(&mut self, engine: &TicTacToe) {
+        self.player = self.player.get_next();
+        self.message = None;
+        self.evaluate(engine);
+    }
+
+    pub fn export(&mut self, engine: &TicTacToe) {
+        self.message = Some(engine.format_position(&self.board, &self.player));
+    }
+
+    pub fn import(&mut self, engine: &TicTacToe) {
+        let Some(input) = self.input.take() else {
+            return;
+        };
+
+        match TicTacToe::parse_position(&input) {
+            Ok(position)
+                if position.size != engine.size || position.win_length != engine.win_length =>
+            {
+                self.message = Some(format!(
+                    "expected a {}x{} board with {} in a row, got {}x{} with {} in a row",
+                    engine.size,
+                    engine.size,
+                    engine.win_length,
+                    position.size,
+                    position.size,
+                    position.win_length
+                ));
+            }
+            Ok(position) => {
+                self.board = position.board;
+                self.player = position.player;
+                self.message = None;
+                self.evaluate(engine);
+            }
+            Err(error) => self.message = Some(error),
+        }
+    }
+
+    fn evaluate(&mut self, engine: &TicTacToe) {
+        self.result = engine.get_game_result(&self.board);
+        self.outcome = None;
+        self.move_outcomes = vec![];
+        if let Err(error) = engine.validate_position(&self.board, &self.player) {
+            self.message = Some(error);
+            return;
+        }
+        if self.result != GameResult::Playing {
+            return;
+        }
+
+        let (move_outcomes, _) = engine.get_move_outcomes(&self.board, &self.player, -1);
+        self.outcome = move_outcomes.first().map(|(_, _, outcome)| *outcome);
+        self.move_outcomes = move_outcomes
+            .into_iter()
+            .map(|(index, _, outcome)| (index, outcome))
+            .collect();
+    }
+}
@@ -0,0 +1,125 @@
+use std::ops::{BitAnd, BitOr, Not};
+
+use crate::entities::{Board, Cell, Player};
+
+const WORDS: usize = 4;
+pub const MAX_CELLS: usize = WORDS * 64;
+
+/// one bit per cell, cell `i` being bit `i`, boards go up to 16x16
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Mask([u64; WORDS]);
+
+impl Mask {
+    /// the first `count` cells
+    pub fn get_first(count: usize) -> Self {
+        let mut mask = Mask::default();
+        for (word, bits) in mask.0.iter_mut().enumerate() {
+            let start = word * 64;
+            *bits = match count.saturating_sub(start) {
+                0 => 0,
+                1..=63 => u64::MAX >> (64 - (count - start)),
+                _ => u64::MAX,
+            };
+        }
+        mask
+    }
+
+    pub fn from_cells(cells: impl IntoIterator<Item = usize>) -> Self {
+        let mut mask = Mask::default();
+        for cell in cells {
+            mask.set(cell);
+        }
+        mask
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&bits| bits == 0)
+    }
+
+    pub fn get_count(&self) -> usize {
+        self.0.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// indexes of the set bits, lowest first
+    pub fn get_cells(self) -> impl Iterator<Item = usize> {
+        self.0.into_iter().enumerate().flat_map(|(word, mut bits)| {
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let index = word * 64 + bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(index)
+            })
+        })
+    }
+}
+
+impl BitAnd for Mask {
+    type Output = Mask;
+
+    fn bitand(self, other: Mask) -> Mask {
+        Mask(std::array::from_fn(|word| self.0[word] & other.0[word]))
+    }
+}
+
+impl BitOr for Mask {
+    type Output = Mask;
+
+    fn bitor(self, other: Mask) -> Mask {
+        Mask(std::array::from_fn(|word| self.0[word] | other.0[word]))
+    }
+}
+
+impl Not for Mask {
+    type Output = Mask;
+
+    fn not(self) -> Mask {
+        Mask(self.0.map(|bits| !bits))
+    }
+}
+
+/// the cells of each player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Bitboard {
+    /// marks of each player, in the order of `Player::ALL`
+    pub marks: [Mask; Player::COUNT],
+}
+
+impl Bitboard {
+    pub fn from_board(board: &Board) -> Self {
+        let mut bitboard = Bitboard::default();
+        for (index, cell) in board.iter().enumerate() {
+            if let Cell::Mark(player) = cell {
+                bitboard.set(index, player);
+            }
+        }
+        bitboard
+    }
+
+    pub fn get_marks(&self, player: &Player) -> Mask {
+        self.marks[player.get_index()]
+    }
+
+    pub fn get_occupied(&self) -> Mask {
+        self.marks
+            .into_iter()
+            .fold(Mask::default(), |occupied, marks| occupied | marks)
+    }
+
+    pub fn set(&mut self, index: usize, player: &Player) {
+        self.marks[player.get_index()].set(index);
+    }
+
+    pub fn clear(&mut self, index: usize, player: &Player) {
+        self.marks[player.get_index()].clear(index);
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::{
+    constants::{MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+    entities::{Difficulty, Player, Starter, Strategy, Variant},
+    keybindings::KeyPreset,
+    theme::ThemePreset,
+};
+
+/// Terminal based Tic-Tac-Toe Game.
+///
+/// Options override the values of the config file. Passing `--side` skips the player selection
+/// and launches straight into the game.
+#[derive(Debug, Default, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Config file to read instead of `$XDG_CONFIG_HOME/tictactoe-tui/config.toml`
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Side you play, skips the player selection
+    #[arg(long)]
+    pub side: Option<Player>,
+
+    /// How hard the computer plays
+    #[arg(long)]
+    pub difficulty: Option<Difficulty>,
+
+    /// Moves the computer looks ahead, replaces the depth of the difficulty. Big boards still cut
+    /// the search short, the positions it stops at are scored by their open lines
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub depth: Option<u16>,
+
+    /// Milliseconds the computer thinks for each move, searching one move deeper at a time while
+    /// there's time left. Replaces the difficulty and `--depth`
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "depth")]
+    pub think_time: Option<u64>,
+
+    /// How the computer searches its moves [default: minimax]
+    #[arg(long)]
+    pub strategy: Option<Strategy>,
+
+    /// Random games the Monte Carlo strategy plays for each move, unless it thinks for a time
+    /// [default: 10000]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "think_time")]
+    pub iterations: Option<u32>,
+
+    /// Threads the computer searches with [default: one per core]
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: Option<u16>,
+
+    /// Who makes the first move
+    #[arg(long)]
+    pub starts: Option<Starter>,
+
+    /// Number of rows and columns of the board
+    #[arg(long, value_parser = clap::value_parser!(u16).range(MIN_BOARD_SIZE as i64..=MAX_BOARD_SIZE as i64))]
+    pub size: Option<u16>,
+
+    /// Marks in a row needed to complete a line, from 3 up to the board size [default: size, at most 4]
+    #[arg(long, value_parser = clap::value_parser!(u16).range(3..=MAX_BOARD_SIZE as i64))]
+    pub win_length: Option<u16>,
+
+    /// Practice mode, warns before a move that loses a drawn or won game
+    #[arg(long)]
+    pub coach: bool,
+
+    /// Rules of the game
+    #[arg(long)]
+    pub variant: Option<Variant>,
+
+    /// Moves played before the game starts, alternating from whoever starts. Cells are numbered
+    /// from 0, left to right then top to bottom, e.g. `4,0,8`
+    #[arg(long, value_name = "CELLS", value_delimiter = ',')]
+    pub moves: Option<Vec<usize>>,
+
+    /// Position to start every game from: the rows separated by `/`, the side to move, the size
+    /// and the win length, e.g. `x../.o./... o 3 3`. Replaces `--size`, `--win-length` and `--moves`
+    #[arg(long, conflicts_with_all = ["size", "win_length", "moves"])]
+    pub position: Option<String>,
+
+    /// Seed of the computer's random choices, the seed of every game is printed on exit so it
+    /// can be replayed exactly [default: random]
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Colour theme, replaces the `[theme]` table of the config file
+    #[arg(long)]
+    pub theme: Option<ThemePreset>,
+
+    /// Key bindings, replaces the `[keys]` table of the config file
+    #[arg(long)]
+    pub keys: Option<KeyPreset>,
+
+    /// Solves every 3x3 position and writes the table embedded in the binary
+    #[arg(long, value_name = "FILE", hide = true)]
+    pub generate_solved_table: Option<PathBuf>,
+}
@@ -0,0 +1,105 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::rngs::StdRng;
+
+use crate::{
+    entities::{Board, GameResult, Player, SearchReport, Strategy},
+    mcts::MonteCarlo,
+    tictactoe::TicTacToe,
+};
+
+/// how the computer searches and for how long
+#[derive(Debug, Clone, Copy)]
+pub struct Strength {
+    pub strategy: Strategy,
+    /// moves minimax looks ahead, -1 for as deep as the board allows
+    pub max_depth: i32,
+    /// how long the computer thinks for each move, replaces `max_depth` and `iterations`
+    pub think_time: Option<Duration>,
+    /// random games the Monte Carlo search plays for each move
+    pub iterations: u32,
+}
+
+/// cell the computer plays, with what its search did
+pub type ComputerMove = (usize, SearchReport);
+
+/// search of the computer's move on a worker thread, cancelled when dropped
+#[derive(Debug)]
+pub struct ComputerSearch {
+    receiver: Receiver<Option<ComputerMove>>,
+    cancel: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl ComputerSearch {
+    pub fn start(
+        engine: TicTacToe,
+        board: Board,
+        player: Player,
+        strength: Strength,
+        mut rng: StdRng,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let worker_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let computer_move = match (strength.strategy, strength.think_time) {
+                (Strategy::Mcts, think_time) => {
+                    if engine.get_game_result(&board) != GameResult::Playing {
+                        None
+                    } else {
+                        MonteCarlo::new(&engine, &board, player).search(
+                            strength.iterations,
+                            think_time,
+                            &worker_cancel,
+                            &mut rng,
+                        )
+                    }
+                }
+                (Strategy::Minimax, Some(think_time)) => {
+                    engine.get_timed_best_move(&board, player, think_time, &worker_cancel, &mut rng)
+                }
+                (Strategy::Minimax, None) => {
+                    let max_depth = engine.get_search_depth(&board, strength.max_depth);
+                    engine.get_best_move(&board, player, max_depth, &worker_cancel, &mut rng)
+                }
+            };
+            // Nobody is waiting for a cancelled search
+            let _ = sender.send(computer_move);
+        });
+
+        ComputerSearch {
+            receiver,
+            cancel,
+            started: Instant::now(),
+        }
+    }
+
+    /// the move once the search is over, `Some(None)` when there was none to play
+    pub fn try_get_move(&self) -> Option<Option<ComputerMove>> {
+        match self.receiver.try_recv() {
+            Ok(computer_move) => Some(computer_move),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(None),
+        }
+    }
+
+    pub fn get_elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl Drop for ComputerSearch {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{env, fmt, fs, io, num::NonZeroUsize, path::PathBuf, thread, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    cli::Cli,
+    constants::{DEFAULT_BOARD_SIZE, DEFAULT_ITERATIONS, MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+    entities::{Cell, Difficulty, GameResult, Player, Position, Starter, Strategy, Variant},
+    keybindings::{InvalidKey, KeyBindings, KeysConfig},
+    theme::{InvalidColor, Symbols, SymbolsConfig, Theme, ThemeConfig},
+    tictactoe::TicTacToe,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ConfigFile {
+    pub side: Option<Player>,
+    pub difficulty: Option<Difficulty>,
+    pub depth: Option<u16>,
+    pub think_time: Option<u64>,
+    pub strategy: Option<Strategy>,
+    pub iterations: Option<u32>,
+    pub threads: Option<u16>,
+    pub starts: Option<Starter>,
+    pub size: Option<u16>,
+    pub win_length: Option<u16>,
+    pub variant: Option<Variant>,
+    pub coach: Option<bool>,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub keys: KeysConfig,
+    #[serde(default)]
+    pub symbols: SymbolsConfig,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub player: Player,
+    /// moves the computer looks ahead, -1 for as deep as the board allows
+    pub max_depth: i32,
+    /// how long the computer thinks for each move, replaces `max_depth`
+    pub think_time: Option<Duration>,
+    pub strategy: Strategy,
+    /// random games the Monte Carlo strategy plays for each move, unless it has a `think_time`
+    pub iterations: u32,
+    pub threads: usize,
+    pub starter: Starter,
+    pub size: u16,
+    pub win_length: u16,
+    pub variant: Variant,
+    pub coach: bool,
+    pub position: Option<Position>,
+    pub moves: Vec<usize>,
+    pub seed: Option<u64>,
+    /// the side was picked on the command line, so the game starts right away
+    pub skip_select_player: bool,
+    pub theme: Theme,
+    pub keys: KeyBindings,
+    pub symbols: Symbols,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Theme(PathBuf, InvalidColor),
+    Keys(PathBuf, InvalidKey),
+    Invalid(PathBuf, String),
+    Options(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, error) => {
+                write!(f, "could not read {}: {}", path.display(), error)
+            }
+            ConfigError::Parse(path, error) => {
+                write!(f, "could not parse {}:\n{}", path.display(), error)
+            }
+            ConfigError::Theme(path, error) => write!(f, "{}: {}", path.display(), error),
+            ConfigError::Keys(path, error) => write!(f, "{}: {}", path.display(), error),
+            ConfigError::Invalid(path, error) => write!(f, "{}: {}", path.display(), error),
+            ConfigError::Options(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Config {
+    /// loads `$XDG_CONFIG_HOME/tictactoe-tui/config.toml` (or `--config`) and applies the CLI on top,
+    /// a missing default file means every default
+    pub fn load(cli: &Cli) -> Result<Config, ConfigError> {
+        let Some(path) = cli.config.clone().or_else(Config::get_path) else {
+            return Config::from_file(ConfigFile::default(), cli, PathBuf::new());
+        };
+
+        let file = match fs::read_to_string(&path) {
+            Ok(content) => {
+                toml::from_str(&content).map_err(|error| ConfigError::Parse(path.clone(), error))?
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound && cli.config.is_none() => {
+                ConfigFile::default()
+            }
+            Err(error) => return Err(ConfigError::Read(path, error)),
+        };
+
+        Config::from_file(file, cli, path)
+    }
+
+    pub fn get_path() -> Option<PathBuf> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_dir.join("tictactoe-tui").join("config.toml"))
+    }
+
+    fn from_file(mut file: ConfigFile, cli: &Cli, path: PathBuf) -> Result<Config, ConfigError> {
+        // A preset given on the command line replaces the whole table of the file
+        if let Some(preset) = cli.theme {
+            file.theme = ThemeConfig {
+                preset: Some(preset),
+                ..ThemeConfig::default()
+            };
+        }
+        if let Some(preset) = cli.keys {
+            file.keys = KeysConfig {
+                preset: Some(preset),
+                ..KeysConfig::default()
+            };
+        }
+
+        let position = cli
+            .position
+            .as_deref()
+            .map(TicTacToe::parse_position)
+            .transpose()
+            .map_err(|error| ConfigError::Options(format!("invalid `--position`: {}", error)))?;
+
+        // The position decides the board, over the config file
+        let size = position
+            .as_ref()
+            .map(|position| position.size)
+            .or(cli.size)
+            .or(file.size)
+            .unwrap_or(DEFAULT_BOARD_SIZE);
+        if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+            return Err(ConfigError::Invalid(
+                path,
+                format!(
+                    "invalid `size` {} (expected {} to {})",
+                    size, MIN_BOARD_SIZE, MAX_BOARD_SIZE
+                ),
+            ));
+        }
+
+        let win_length = position
+            .as_ref()
+            .map(|position| position.win_length)
+            .or(cli.win_length)
+            .or(file.win_length)
+            .unwrap_or(TicTacToe::get_default_win_length(size));
+        if !(3..=size).contains(&win_length) {
+            // Blame the config file when the win length came from it
+            if position.is_none() && cli.win_length.is_none() {
+                return Err(ConfigError::Invalid(
+                    path,
+                    format!(
+                        "invalid `win-length` {} for a {}x{} board (expected 3 to {})",
+                        win_length, size, size, size
+                    ),
+                ));
+            }
+            return Err(ConfigError::Options(format!(
+                "invalid win length {} for a {}x{} board (expected 3 to {})",
+                win_length, size, size, size
+            )));
+        }
+
+        if file.depth == Some(0) {
+            return Err(ConfigError::Invalid(
+                path,
+                "invalid `depth` 0 (expected at least 1)".to_string(),
+            ));
+        }
+        if file.think_time == Some(0) {
+            return Err(ConfigError::Invalid(
+                path,
+                "invalid `think-time` 0 (expected at least 1 ms)".to_string(),
+            ));
+        }
+
+        if file.iterations == Some(0) {
+            return Err(ConfigError::Invalid(
+                path,
+                "invalid `iterations` 0 (expected at least 1)".to_string(),
+            ));
+        }
+
+        if file.threads == Some(0) {
+            return Err(ConfigError::Invalid(
+                path,
+                "invalid `threads` 0 (expected at least 1)".to_string(),
+            ));
+        }
+        let threads = match cli.threads.or(file.threads) {
+            Some(threads) => threads as usize,
+            None => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        };
+
+        // A strength given on the command line replaces those of the file
+        let think_time = if cli.think_time.is_some()
+            || cli.depth.is_some()
+            || cli.difficulty.is_some()
+            || cli.iterations.is_some()
+        {
+            cli.think_time
+        } else {
+            file.think_time
+        };
+        let iterations = if cli.think_time.is_some() {
+            DEFAULT_ITERATIONS
+        } else {
+            cli.iterations
+                .or(file.iterations)
+                .unwrap_or(DEFAULT_ITERATIONS)
+        };
+        let max_depth = match (cli.depth, cli.difficulty) {
+            (Some(depth), _) => depth as i32,
+            (None, Some(difficulty)) => difficulty.get_max_depth(),
+            (None, None) => match file.depth {
+                Some(depth) => depth as i32,
+                None => file
+                    .difficulty
+                    .unwrap_or(Difficulty::Perfect)
+                    .get_max_depth(),
+            },
+        };
+
+        let variant = cli.variant.or(file.variant).unwrap_or(Variant::Standard);
+        let moves = cli.moves.clone().unwrap_or_default();
+        Config::validate_moves(&TicTacToe::new(size, win_length, variant), &moves)
+            .map_err(ConfigError::Options)?;
+
+        let theme = Theme::from_config(&file.theme)
+            .map_err(|error| ConfigError::Theme(path.clone(), error))?;
+        let keys = KeyBindings::from_config(&file.keys)
+            .map_err(|error| ConfigError::Keys(path.clone(), error))?;
+        keys.validate()
+            .map_err(|error| ConfigError::Invalid(path.clone(), error))?;
+        let symbols = Symbols::from_config(&file.symbols)
+            .map_err(|error| ConfigError::Invalid(path.clone(), error))?;
+
+        Ok(Config {
+            player: cli.side.clone().or(file.side).unwrap_or(Player::O),
+            max_depth,
+            think_time: think_time.map(Duration::from_millis),
+            strategy: cli.strategy.or(file.strategy).unwrap_or(Strategy::Minimax),
+            iterations,
+            threads,
+            starter: cli.starts.or(file.starts).unwrap_or(Starter::Human),
+            size,
+            win_length,
+            variant,
+            coach: cli.coach || file.coach.unwrap_or(false),
+            position,
+            moves,
+            seed: cli.seed,
+            skip_select_player: cli.side.is_some(),
+            theme,
+            keys,
+            symbols,
+        })
+    }
+
+    fn validate_moves(engine: &TicTacToe, moves: &[usize]) -> Result<(), String> {
+        let mut board = engine.get_empty_board();
+        let mut player = Player::X;
+
+        for (turn, &index) in moves.iter().enumerate() {
+            if engine.get_game_result(&board) != GameResult::Playing {
+                return Err(format!(
+                    "invalid move {} in `--moves`, the game is already over after {} moves",
+                    index, turn
+                ));
+            }
+            if index >= board.len() {
+                return Err(format!(
+                    "invalid move {} in `--moves` (expected a cell from 0 to {})",
+                    index,
+                    board.len() - 1
+                ));
+            }
+            if !board[index].is_empty() {
+                return Err(format!(
+                    "invalid move {} in `--moves`, the cell is already taken",
+                    index
+                ));
+            }
+
+            board[index] = Cell::Mark(player.clone());
+            player = player.get_next();
+        }
+
+        Ok(())
+    }
+}
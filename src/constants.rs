@@ -1,2 +1,14 @@
-pub const BOARD_SIZE: u16 = 3;
+use std::time::Duration;
+
+pub const DEFAULT_BOARD_SIZE: u16 = 3;
+pub const MIN_BOARD_SIZE: u16 = 3;
+pub const MAX_BOARD_SIZE: u16 = 15;
 pub const CELL_SIZE: u16 = 10;
+pub const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(80);
+pub const MAX_SEARCH_NODES: u64 = 1_000_000;
+pub const DEFAULT_ITERATIONS: u32 = 10_000;
+/// static evaluations of unfinished positions stay below the score of the slowest win
+pub const MAX_EVALUATION: i32 = 50;
+/// line weight total scored at half of `MAX_EVALUATION`
+pub const EVALUATION_SCALE: i64 = 100;
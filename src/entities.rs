@@ -1,15 +1,36 @@
-use ratatui::style::Color;
+use std::time::Duration;
 
-#[derive(Debug, PartialEq, Clone)]
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::constants::MAX_EVALUATION;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum Player {
     O,
     X,
-    None,
 }
 
-pub type Board = Vec<Player>;
+/// content of a board cell
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub enum Cell {
+    Empty,
+    Mark(Player),
+}
 
-#[derive(Debug, PartialEq)]
+pub type Board = Vec<Cell>;
+
+/// board and side to move read from a position string
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub board: Board,
+    pub player: Player,
+    pub size: u16,
+    pub win_length: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum GameResult {
     Playing,
     Win(Player),
@@ -17,27 +38,230 @@ pub enum GameResult {
 }
 
 impl Player {
-    pub fn get_text(&self) -> String {
+    /// every side in turn order, boards, keys and line counts hold one entry per side
+    pub const ALL: [Player; 2] = [Player::X, Player::O];
+    pub const COUNT: usize = Player::ALL.len();
+
+    /// position of the player in `Player::ALL`
+    pub fn get_index(&self) -> usize {
+        match self {
+            Player::X => 0,
+            Player::O => 1,
+        }
+    }
+
+    /// the player moving `steps` turns after this one
+    pub fn get_rotated(&self, steps: usize) -> Player {
+        Player::ALL[(self.get_index() + steps) % Player::COUNT].clone()
+    }
+
+    /// the player moving after this one
+    pub fn get_next(&self) -> Player {
+        self.get_rotated(1)
+    }
+}
+
+impl Cell {
+    pub fn is_empty(&self) -> bool {
+        self == &Cell::Empty
+    }
+
+    pub fn is_marked_by(&self, player: &Player) -> bool {
+        matches!(self, Cell::Mark(mark) if mark == player)
+    }
+
+    /// the cell with the mark of the player moving `steps` turns later
+    pub fn get_rotated(&self, steps: usize) -> Cell {
+        match self {
+            Cell::Empty => Cell::Empty,
+            Cell::Mark(player) => Cell::Mark(player.get_rotated(steps)),
+        }
+    }
+
+    pub fn get_player(&self) -> Option<&Player> {
         match self {
-            Player::X => "X".to_string(),
-            Player::O => "O".to_string(),
-            Player::None => " ".to_string(),
+            Cell::Empty => None,
+            Cell::Mark(player) => Some(player),
+        }
+    }
+}
+
+/// theoretical result of a move or position for the side it is scored for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// wins within that many of its own moves
+    Win(i32),
+    Draw,
+    /// loses within that many of the opponent's moves
+    Loss(i32),
+    /// the search stopped at its depth limit before the game ended
+    Unknown,
+}
+
+impl Outcome {
+    /// `score` as returned by `TicTacToe::get_move_scores`, a 0 is only a draw when the search reached every end of the game
+    /// and evaluations of unfinished positions are unclear
+    pub fn from_score(score: i32, is_exhaustive: bool) -> Outcome {
+        if score > MAX_EVALUATION {
+            Outcome::Win((100 - score + 1) / 2)
+        } else if score < -MAX_EVALUATION {
+            Outcome::Loss((100 + score + 1) / 2)
+        } else if score == 0 && is_exhaustive {
+            Outcome::Draw
+        } else {
+            Outcome::Unknown
         }
     }
 
-    pub fn get_color(&self) -> Color {
+    pub fn get_description(&self) -> String {
         match self {
-            Player::X => Color::Red,
-            Player::O => Color::Green,
-            Player::None => Color::DarkGray,
+            Outcome::Win(1) => "wins with the next move".to_string(),
+            Outcome::Win(moves) => format!("wins in {} moves", moves),
+            Outcome::Draw => "draws".to_string(),
+            Outcome::Loss(1) => "loses to the next reply".to_string(),
+            Outcome::Loss(moves) => format!("loses in {} moves", moves),
+            Outcome::Unknown => "is unclear".to_string(),
         }
     }
 
-    pub fn get_opponent(&self) -> Player {
+    pub fn get_label(&self) -> String {
         match self {
-            Player::X => Player::O,
-            Player::O => Player::X,
-            Player::None => Player::None,
+            Outcome::Win(moves) => format!("W{}", moves),
+            Outcome::Draw => "D".to_string(),
+            Outcome::Loss(moves) => format!("L{}", moves),
+            Outcome::Unknown => "?".to_string(),
+        }
+    }
+}
+
+/// what the search of the computer's move did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchReport {
+    /// depth of the last search that finished, -1 when it reached every end of the game
+    pub depth: i32,
+    /// positions searched, or random games played by a Monte Carlo search
+    pub nodes: u64,
+    /// positions whose score came from the transposition table
+    pub table_hits: u64,
+    pub elapsed: Duration,
+    /// moves both sides are expected to play, starting with the computer's
+    pub principal_variation: Vec<usize>,
+}
+
+/// how a move compares to the best move available, by theoretical outcome
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveQuality {
+    Best,
+    /// same result as the best move, only slower to win or quicker to lose
+    Ok,
+    /// gave away a win
+    Mistake,
+    /// turned a win or a draw into a loss
+    Blunder,
+}
+
+impl MoveQuality {
+    pub fn from_outcomes(best: Outcome, played: Outcome) -> MoveQuality {
+        let get_rank = |outcome: Outcome| match outcome {
+            Outcome::Win(_) => 2,
+            Outcome::Draw | Outcome::Unknown => 1,
+            Outcome::Loss(_) => 0,
+        };
+
+        if played == best {
+            MoveQuality::Best
+        } else if get_rank(played) == get_rank(best) {
+            MoveQuality::Ok
+        } else if let Outcome::Loss(_) = played {
+            MoveQuality::Blunder
+        } else {
+            MoveQuality::Mistake
+        }
+    }
+
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            MoveQuality::Best => "best",
+            MoveQuality::Ok => "ok",
+            MoveQuality::Mistake => "mistake",
+            MoveQuality::Blunder => "blunder",
+        }
+    }
+}
+
+/// why an empty cell matters right now, see `TicTacToe::get_threats`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threat {
+    /// completes a line
+    Win,
+    /// the opponent completes a line there
+    Block,
+    /// leaves two lines one mark short at once
+    Fork,
+    /// completes a line, which loses in misère
+    Lose,
+    /// the opponent completes a line there and loses in misère, better left empty
+    Avoid,
+}
+
+impl Threat {
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            Threat::Win => "win",
+            Threat::Block => "block",
+            Threat::Fork => "fork",
+            Threat::Lose => "lose",
+            Threat::Avoid => "avoid",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineKind {
+    Row,
+    Column,
+    Diagonal,
+}
+
+impl LineKind {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            LineKind::Row => "row",
+            LineKind::Column => "column",
+            LineKind::Diagonal => "diagonal",
+        }
+    }
+}
+
+/// tactical reason behind a move, see `TicTacToe::get_motif`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Motif {
+    Win,
+    /// completed a line in misère, every other move lost too
+    Forced,
+    /// stopped the opponent from completing the line
+    Block(LineKind),
+    /// threatens to complete two lines at once
+    Fork,
+    /// threatens to complete the line
+    Threat(LineKind),
+    Centre,
+    Corner,
+    Other,
+}
+
+impl Motif {
+    /// what the computer did, e.g. "blocked your row"
+    pub fn get_description(&self) -> String {
+        match self {
+            Motif::Win => "won the game".to_string(),
+            Motif::Forced => "had no safe move left".to_string(),
+            Motif::Block(kind) => format!("blocked your {}", kind.get_name()),
+            Motif::Fork => "created a fork".to_string(),
+            Motif::Threat(kind) => format!("threatens a {}", kind.get_name()),
+            Motif::Centre => "took the centre".to_string(),
+            Motif::Corner => "took a corner".to_string(),
+            Motif::Other => "played".to_string(),
         }
     }
 }
@@ -47,4 +271,60 @@ pub enum GameState {
     SelectPlayer,
     Playing,
     GameOver(GameResult),
+    Analysis,
+    Review,
+    Explorer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// Looks one move ahead, takes a win when it sees one
+    Easy,
+    /// Looks two moves ahead, also blocks your wins
+    Medium,
+    /// Looks four moves ahead
+    Hard,
+    /// Searches as deep as the board allows
+    Perfect,
+}
+
+impl Difficulty {
+    pub fn get_max_depth(&self) -> i32 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 2,
+            Difficulty::Hard => 4,
+            Difficulty::Perfect => -1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// Looks ahead at every move, as deep as the difficulty allows
+    Minimax,
+    /// Plays random games to the end and follows the moves that win most
+    Mcts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Starter {
+    /// You move first
+    Human,
+    /// The computer moves first
+    Computer,
+    /// A coin flip before every game
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    /// Completing a line wins
+    Standard,
+    /// Completing a line loses
+    Misere,
 }
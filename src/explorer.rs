@@ -0,0 +1,196 @@
+use crate::{
+    entities::{Board, Cell, GameResult, Outcome, Player},
+    tictactoe::TicTacToe,
+};
+
+/// move of the explored tree, scored for the player who makes it
+#[derive(Debug)]
+pub struct TreeNode {
+    pub index: usize,
+    pub player: Player,
+    /// as in `TicTacToe::get_move_outcomes`
+    pub score: i32,
+    pub outcome: Outcome,
+    /// one of the moves `get_best_move` picks from as deep as the tree is searched
+    pub is_best: bool,
+    /// replies, best first, `None` until the move is first expanded
+    pub children: Option<Vec<TreeNode>>,
+    pub is_expanded: bool,
+}
+
+/// node of the tree as shown, with the positions of its ancestors among their siblings
+#[derive(Debug)]
+pub struct TreeRow<'a> {
+    pub path: Vec<usize>,
+    pub node: &'a TreeNode,
+}
+
+/// minimax tree of the position, expanded one move at a time
+#[derive(Debug)]
+pub struct Explorer {
+    pub board: Board,
+    /// side to move
+    pub player: Player,
+    /// depth every move is searched to, as for `TicTacToe::get_search_depth`
+    pub max_depth: i32,
+    pub moves: Vec<TreeNode>,
+    /// positions among their siblings of the selected node and its ancestors
+    pub selected: Vec<usize>,
+}
+
+impl Explorer {
+    pub fn new(engine: &TicTacToe, board: Board, player: Player, max_depth: i32) -> Self {
+        let moves = Explorer::get_nodes(engine, &board, &player, max_depth);
+        Explorer {
+            board,
+            player,
+            max_depth,
+            selected: if moves.is_empty() { vec![] } else { vec![0] },
+            moves,
+        }
+    }
+
+    /// scored moves of `player`, best first
+    fn get_nodes(
+        engine: &TicTacToe,
+        board: &Board,
+        player: &Player,
+        max_depth: i32,
+    ) -> Vec<TreeNode> {
+        let (move_outcomes, _) = engine.get_move_outcomes(board, player, max_depth);
+        let best = move_outcomes.first().map_or(0, |(_, score, _)| *score);
+
+        move_outcomes
+            .into_iter()
+            .map(|(index, score, outcome)| {
+                let mut board = board.clone();
+                board[index] = Cell::Mark(player.clone());
+                // A move that ends the game has no replies
+                let is_over = engine.get_move_result(&board, index) != GameResult::Playing;
+                TreeNode {
+                    index,
+                    player: player.clone(),
+                    score,
+                    outcome,
+                    is_best: score == best,
+                    children: if is_over { Some(vec![]) } else { None },
+                    is_expanded: false,
+                }
+            })
+            .collect()
+    }
+
+    /// nodes of expanded moves, depth first
+    pub fn get_rows(&self) -> Vec<TreeRow<'_>> {
+        let mut rows = vec![];
+        Explorer::push_rows(&self.moves, &mut vec![], &mut rows);
+        rows
+    }
+
+    fn push_rows<'a>(nodes: &'a [TreeNode], path: &mut Vec<usize>, rows: &mut Vec<TreeRow<'a>>) {
+        for (position, node) in nodes.iter().enumerate() {
+            path.push(position);
+            rows.push(TreeRow {
+                path: path.clone(),
+                node,
+            });
+            if let (true, Some(children)) = (node.is_expanded, &node.children) {
+                Explorer::push_rows(children, path, rows);
+            }
+            path.pop();
+        }
+    }
+
+    /// the selected move and those before it, from the first
+    pub fn get_variation(&self) -> Vec<&TreeNode> {
+        let mut variation: Vec<&TreeNode> = vec![];
+        for position in &self.selected {
+            let nodes = match variation.last() {
+                Some(node) => node.children.as_deref().unwrap_or_default(),
+                None => &self.moves,
+            };
+            let Some(node) = nodes.get(*position) else {
+                break;
+            };
+            variation.push(node);
+        }
+        variation
+    }
+
+    fn get_selected_mut(&mut self) -> Option<&mut TreeNode> {
+        let (first, rest) = self.selected.split_first()?;
+        let mut node = self.moves.get_mut(*first)?;
+        for position in rest {
+            node = node.children.as_mut()?.get_mut(*position)?;
+        }
+        Some(node)
+    }
+
+    /// board once the selected move and those before it are played
+    pub fn get_board(&self) -> Board {
+        let mut board = self.board.clone();
+        for node in self.get_variation() {
+            board[node.index] = Cell::Mark(node.player.clone());
+        }
+        board
+    }
+
+    pub fn select_previous(&mut self) {
+        self.select_row(|row| row.saturating_sub(1));
+    }
+
+    pub fn select_next(&mut self) {
+        self.select_row(|row| row + 1);
+    }
+
+    fn select_row(&mut self, get_row: impl Fn(usize) -> usize) {
+        let rows = self.get_rows();
+        let Some(row) = rows.iter().position(|row| row.path == self.selected) else {
+            return;
+        };
+        if let Some(row) = rows.get(get_row(row)) {
+            self.selected = row.path.clone();
+        }
+    }
+
+    /// shows the replies to the selected move, or selects the best one when they're shown
+    pub fn expand(&mut self, engine: &TicTacToe) {
+        let board = self.get_board();
+        let max_depth = self.max_depth;
+        let Some(node) = self.get_selected_mut() else {
+            return;
+        };
+
+        if node.is_expanded {
+            if node
+                .children
+                .as_ref()
+                .is_some_and(|children| !children.is_empty())
+            {
+                self.selected.push(0);
+            }
+            return;
+        }
+        if node.children.is_none() {
+            let player = node.player.get_next();
+            node.children = Some(Explorer::get_nodes(engine, &board, &player, max_depth));
+        }
+        node.is_expanded = node
+            .children
+            .as_ref()
+            .is_some_and(|children| !children.is_empty());
+    }
+
+    /// hides the replies to the selected move, or selects the move it replies to when they're hidden
+    pub fn collapse(&mut self) {
+        if let Some(node) = self.get_selected_mut() {
+            if node.is_expanded {
+                node.is_expanded = false;
+                return;
+            }
+        }
+        if self.selected.len() > 1 {
+            self.selected.pop();
+        }
+    }
+}
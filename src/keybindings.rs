@@ -0,0 +1,382 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    Restart,
+    SelectPlayer,
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Hint,
+    Heatmap,
+    Threats,
+    Stats,
+    Analysis,
+    Review,
+    Explorer,
+    PlaceX,
+    PlaceO,
+    ClearCell,
+    SideToMove,
+    Import,
+    Export,
+    LosingMove,
+    Back,
+    // 0 1 2
+    // 3 4 5
+    // 6 7 8
+    Cell(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyPreset {
+    /// Arrows + enter
+    Default,
+    /// hjkl + space
+    Vim,
+    /// Number keys play a cell, 7-8-9 on top
+    Numpad,
+    /// WASD + space, `p` selects the player
+    Wasd,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    pub quit: Vec<KeyCode>,
+    pub restart: Vec<KeyCode>,
+    pub select_player: Vec<KeyCode>,
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub confirm: Vec<KeyCode>,
+    pub hint: Vec<KeyCode>,
+    pub heatmap: Vec<KeyCode>,
+    pub threats: Vec<KeyCode>,
+    pub stats: Vec<KeyCode>,
+    pub analysis: Vec<KeyCode>,
+    pub review: Vec<KeyCode>,
+    pub explorer: Vec<KeyCode>,
+    /// editing keys of the analysis screen
+    pub place_x: Vec<KeyCode>,
+    pub place_o: Vec<KeyCode>,
+    pub clear_cell: Vec<KeyCode>,
+    pub side_to_move: Vec<KeyCode>,
+    pub import: Vec<KeyCode>,
+    pub export: Vec<KeyCode>,
+    /// jumps to the last blunder of the review screen
+    pub losing_move: Vec<KeyCode>,
+    /// leaves the analysis, review and tree screens
+    pub back: Vec<KeyCode>,
+    /// number keys play a cell directly, laid out like a numpad (7-8-9 on top)
+    pub numpad: bool,
+}
+
+/// `[keys]` table of the config file, every action overrides the preset
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct KeysConfig {
+    pub preset: Option<KeyPreset>,
+    pub quit: Option<Vec<String>>,
+    pub restart: Option<Vec<String>>,
+    pub select_player: Option<Vec<String>>,
+    pub up: Option<Vec<String>>,
+    pub down: Option<Vec<String>>,
+    pub left: Option<Vec<String>>,
+    pub right: Option<Vec<String>>,
+    pub confirm: Option<Vec<String>>,
+    pub hint: Option<Vec<String>>,
+    pub heatmap: Option<Vec<String>>,
+    pub threats: Option<Vec<String>>,
+    pub stats: Option<Vec<String>>,
+    pub analysis: Option<Vec<String>>,
+    pub review: Option<Vec<String>>,
+    pub explorer: Option<Vec<String>>,
+    pub place_x: Option<Vec<String>>,
+    pub place_o: Option<Vec<String>>,
+    pub clear_cell: Option<Vec<String>>,
+    pub side_to_move: Option<Vec<String>>,
+    pub import: Option<Vec<String>>,
+    pub export: Option<Vec<String>>,
+    pub losing_move: Option<Vec<String>>,
+    pub back: Option<Vec<String>>,
+    pub numpad: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct InvalidKey {
+    pub action: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for InvalidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid key `{}` for `keys.{}` (expected a single character or one of `up`, `down`, `left`, `right`, `enter`, `space`, `tab`, `backspace`, `delete`, `esc`)",
+            self.value, self.action
+        )
+    }
+}
+
+impl KeyPreset {
+    pub fn get_bindings(&self) -> KeyBindings {
+        let arrows = KeyBindings {
+            quit: vec![KeyCode::Char('q')],
+            restart: vec![KeyCode::Char('r')],
+            select_player: vec![KeyCode::Char('s')],
+            up: vec![KeyCode::Up],
+            down: vec![KeyCode::Down],
+            left: vec![KeyCode::Left],
+            right: vec![KeyCode::Right],
+            confirm: vec![KeyCode::Enter],
+            hint: vec![KeyCode::Char('?')],
+            heatmap: vec![KeyCode::Char('m')],
+            threats: vec![KeyCode::Char('t')],
+            stats: vec![KeyCode::Char('n')],
+            analysis: vec![KeyCode::Char('e')],
+            review: vec![KeyCode::Char('v')],
+            explorer: vec![KeyCode::Char('g')],
+            place_x: vec![KeyCode::Char('x')],
+            place_o: vec![KeyCode::Char('o')],
+            clear_cell: vec![KeyCode::Char('.'), KeyCode::Backspace, KeyCode::Delete],
+            side_to_move: vec![KeyCode::Tab],
+            import: vec![KeyCode::Char('i')],
+            export: vec![KeyCode::Char('c')],
+            losing_move: vec![KeyCode::Char('b')],
+            back: vec![KeyCode::Esc],
+            numpad: false,
+        };
+
+        match self {
+            KeyPreset::Default => arrows,
+            KeyPreset::Vim => KeyBindings {
+                up: vec![KeyCode::Char('k'), KeyCode::Up],
+                down: vec![KeyCode::Char('j'), KeyCode::Down],
+                left: vec![KeyCode::Char('h'), KeyCode::Left],
+                right: vec![KeyCode::Char('l'), KeyCode::Right],
+                confirm: vec![KeyCode::Char(' '), KeyCode::Enter],
+                ..arrows
+            },
+            KeyPreset::Numpad => KeyBindings {
+                numpad: true,
+                ..arrows
+            },
+            // `s` moves down, so selecting the player moves to `p`
+            KeyPreset::Wasd => KeyBindings {
+                select_player: vec![KeyCode::Char('p')],
+                up: vec![KeyCode::Char('w'), KeyCode::Up],
+                down: vec![KeyCode::Char('s'), KeyCode::Down],
+                left: vec![KeyCode::Char('a'), KeyCode::Left],
+                right: vec![KeyCode::Char('d'), KeyCode::Right],
+                confirm: vec![KeyCode::Char(' '), KeyCode::Enter],
+                ..arrows
+            },
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn from_config(config: &KeysConfig) -> Result<KeyBindings, InvalidKey> {
+        let mut bindings = config.preset.unwrap_or(KeyPreset::Default).get_bindings();
+        let overrides = [
+            ("quit", &config.quit, &mut bindings.quit),
+            ("restart", &config.restart, &mut bindings.restart),
+            (
+                "select-player",
+                &config.select_player,
+                &mut bindings.select_player,
+            ),
+            ("up", &config.up, &mut bindings.up),
+            ("down", &config.down, &mut bindings.down),
+            ("left", &config.left, &mut bindings.left),
+            ("right", &config.right, &mut bindings.right),
+            ("confirm", &config.confirm, &mut bindings.confirm),
+            ("hint", &config.hint, &mut bindings.hint),
+            ("heatmap", &config.heatmap, &mut bindings.heatmap),
+            ("threats", &config.threats, &mut bindings.threats),
+            ("stats", &config.stats, &mut bindings.stats),
+            ("analysis", &config.analysis, &mut bindings.analysis),
+            ("review", &config.review, &mut bindings.review),
+            ("explorer", &config.explorer, &mut bindings.explorer),
+            ("place-x", &config.place_x, &mut bindings.place_x),
+            ("place-o", &config.place_o, &mut bindings.place_o),
+            ("clear-cell", &config.clear_cell, &mut bindings.clear_cell),
+            (
+                "side-to-move",
+                &config.side_to_move,
+                &mut bindings.side_to_move,
+            ),
+            ("import", &config.import, &mut bindings.import),
+            ("export", &config.export, &mut bindings.export),
+            (
+                "losing-move",
+                &config.losing_move,
+                &mut bindings.losing_move,
+            ),
+            ("back", &config.back, &mut bindings.back),
+        ];
+        for (action, values, keys) in overrides {
+            if let Some(values) = values {
+                *keys = values
+                    .iter()
+                    .map(|value| {
+                        KeyBindings::parse_key(value).ok_or_else(|| InvalidKey {
+                            action,
+                            value: value.clone(),
+                        })
+                    })
+                    .collect::<Result<Vec<KeyCode>, InvalidKey>>()?;
+            }
+        }
+        if let Some(numpad) = config.numpad {
+            bindings.numpad = numpad;
+        }
+
+        Ok(bindings)
+    }
+
+    /// rejects a key bound to more than one action, only the first one could ever fire
+    pub fn validate(&self) -> Result<(), String> {
+        let mut actions = vec![
+            ("quit", self.quit.clone()),
+            ("restart", self.restart.clone()),
+            ("select-player", self.select_player.clone()),
+            ("up", self.up.clone()),
+            ("down", self.down.clone()),
+            ("left", self.left.clone()),
+            ("right", self.right.clone()),
+            ("confirm", self.confirm.clone()),
+            ("hint", self.hint.clone()),
+            ("heatmap", self.heatmap.clone()),
+            ("threats", self.threats.clone()),
+            ("stats", self.stats.clone()),
+            ("analysis", self.analysis.clone()),
+            ("review", self.review.clone()),
+            ("explorer", self.explorer.clone()),
+            ("place-x", self.place_x.clone()),
+            ("place-o", self.place_o.clone()),
+            ("clear-cell", self.clear_cell.clone()),
+            ("side-to-move", self.side_to_move.clone()),
+            ("import", self.import.clone()),
+            ("export", self.export.clone()),
+            ("losing-move", self.losing_move.clone()),
+            ("back", self.back.clone()),
+        ];
+        if self.numpad {
+            actions.push(("numpad", ('1'..='9').map(KeyCode::Char).collect()));
+        }
+
+        for (index, (action, keys)) in actions.iter().enumerate() {
+            for (other_action, other_keys) in &actions[index + 1..] {
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key)) {
+                    return Err(format!(
+                        "key {} is bound to both `keys.{}` and `keys.{}`",
+                        KeyBindings::get_label(&[*key]),
+                        action,
+                        other_action
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_action(&self, code: KeyCode) -> Option<Action> {
+        let actions = [
+            (&self.quit, Action::Quit),
+            (&self.restart, Action::Restart),
+            (&self.select_player, Action::SelectPlayer),
+            (&self.up, Action::Up),
+            (&self.down, Action::Down),
+            (&self.left, Action::Left),
+            (&self.right, Action::Right),
+            (&self.confirm, Action::Confirm),
+            (&self.hint, Action::Hint),
+            (&self.heatmap, Action::Heatmap),
+            (&self.threats, Action::Threats),
+            (&self.stats, Action::Stats),
+            (&self.analysis, Action::Analysis),
+            (&self.review, Action::Review),
+            (&self.explorer, Action::Explorer),
+            (&self.place_x, Action::PlaceX),
+            (&self.place_o, Action::PlaceO),
+            (&self.clear_cell, Action::ClearCell),
+            (&self.side_to_move, Action::SideToMove),
+            (&self.import, Action::Import),
+            (&self.export, Action::Export),
+            (&self.losing_move, Action::LosingMove),
+            (&self.back, Action::Back),
+        ];
+        if let Some((_, action)) = actions.iter().find(|(keys, _)| keys.contains(&code)) {
+            return Some(*action);
+        }
+
+        match code {
+            KeyCode::Char(digit @ '1'..='9') if self.numpad => {
+                // 7 8 9
+                // 4 5 6
+                // 1 2 3
+                let digit = digit as u16 - '1' as u16;
+                Some(Action::Cell((2 - digit / 3) * 3 + digit % 3))
+            }
+            _ => None,
+        }
+    }
+
+    /// label of the first key bound to an action, e.g. `<q>`
+    pub fn get_label(keys: &[KeyCode]) -> String {
+        let name = match keys.first() {
+            Some(KeyCode::Char(' ')) => "space".to_string(),
+            Some(KeyCode::Char(char)) => char.to_string(),
+            Some(KeyCode::Up) => "↑".to_string(),
+            Some(KeyCode::Down) => "↓".to_string(),
+            Some(KeyCode::Left) => "←".to_string(),
+            Some(KeyCode::Right) => "→".to_string(),
+            Some(KeyCode::Enter) => "enter".to_string(),
+            Some(KeyCode::Tab) => "tab".to_string(),
+            Some(KeyCode::Backspace) => "backspace".to_string(),
+            Some(KeyCode::Delete) => "delete".to_string(),
+            Some(KeyCode::Esc) => "esc".to_string(),
+            Some(code) => code.to_string().to_lowercase(),
+            None => "unbound".to_string(),
+        };
+        format!("<{}>", name)
+    }
+
+    fn parse_key(value: &str) -> Option<KeyCode> {
+        let mut chars = value.chars();
+        if let (Some(char), None) = (chars.next(), chars.next()) {
+            return Some(KeyCode::Char(char));
+        }
+
+        match value.to_lowercase().as_str() {
+            "up" => Some(KeyCode::Up),
+            "down" => Some(KeyCode::Down),
+            "left" => Some(KeyCode::Left),
+            "right" => Some(KeyCode::Right),
+            "enter" => Some(KeyCode::Enter),
+            "space" => Some(KeyCode::Char(' ')),
+            "tab" => Some(KeyCode::Tab),
+            "backspace" => Some(KeyCode::Backspace),
+            "delete" => Some(KeyCode::Delete),
+            "esc" => Some(KeyCode::Esc),
+            _ => None,
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyPreset::Default.get_bindings()
+    }
+}
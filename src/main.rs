@@ -1,36 +1,77 @@
-use constants::{BOARD_SIZE, CELL_SIZE};
+use analysis::Analysis;
+use clap::Parser;
+use cli::Cli;
+use computer::{ComputerSearch, Strength};
+use config::Config;
+use constants::{CELL_SIZE, SPINNER_FRAMES, SPINNER_INTERVAL};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use entities::{Board, GameResult, GameState, Player};
+use entities::{
+    Board, Cell, GameResult, GameState, Motif, MoveQuality, Outcome, Player, SearchReport, Starter,
+    Strategy, Threat,
+};
+use explorer::Explorer;
+use keybindings::{Action, KeyBindings};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use ratatui::{
     layout::{Alignment, Rect},
-    style::{Color, Stylize},
+    style::{Style, Stylize},
     symbols::border,
-    text::{Line, Text},
+    text::{Line, Span, Text},
     widgets::{
         block::{Position, Title},
-        Block,
+        Block, Padding,
     },
     DefaultTerminal, Frame,
 };
-use std::{
-    cmp::{max, min},
-    collections::HashMap,
-    io,
-};
+use review::Review;
+use std::{cmp::min, io, process};
+use theme::{Symbols, Theme};
 use tictactoe::TicTacToe;
-use widgets::cell_widget::CellWidget;
+use widgets::{board_widget::BoardWidget, cell_widget::CellWidget, tree_widget::TreeWidget};
 
+mod analysis;
+mod bitboard;
+mod cli;
+mod computer;
+mod config;
 mod constants;
 mod entities;
+mod explorer;
 mod helpers;
+mod keybindings;
+mod mcts;
+mod review;
+mod solved;
+mod symmetry;
+mod theme;
 mod tictactoe;
 mod widgets;
+mod zobrist;
 
 fn main() -> io::Result<()> {
+    // the config is validated before the terminal switches to the alternate screen
+    let cli = Cli::parse();
+    if let Some(path) = &cli.generate_solved_table {
+        return solved::generate(path);
+    }
+
+    let config = match Config::load(&cli) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+    };
+
     let mut terminal = ratatui::init();
-    let app = App::new().run(&mut terminal);
+    let mut app = App::new(config);
+    let result = app.run(&mut terminal);
     ratatui::restore();
-    app
+
+    for (index, seed) in app.seeds.iter().enumerate() {
+        println!("game {}: --seed {}", index + 1, seed);
+    }
+    result
 }
 
 #[derive(Debug)]
@@ -39,18 +80,103 @@ pub struct App {
     game_state: GameState,
     selected_index: u16,
     board: Board,
+    /// board the moves of `history` are played from, after the starting position and moves
+    start_board: Board,
+    /// every move of the current game
+    history: Vec<(usize, Player)>,
+    engine: TicTacToe,
+    strength: Strength,
+    starter: Starter,
+    /// position every game starts from, before the moves are played
+    position: Option<entities::Position>,
+    /// starting moves, played before every game
+    moves: Vec<usize>,
+    /// seed given on the command line, used by the first game
+    seed: Option<u64>,
+    /// seed of every game played, the computer's random choices of a game only depend on it
+    seeds: Vec<u64>,
+    rng: StdRng,
+    /// computer's last move and why it was played
+    computer_move: Option<(usize, Motif)>,
+    /// what the search of the computer's last move did
+    search_report: Option<SearchReport>,
+    /// search statistics of the computer's last move, left of the board
+    show_stats: bool,
+    /// search of the computer's move while it thinks
+    computer_search: Option<ComputerSearch>,
+    /// best move for the player, shown until the next move
+    hint: Option<usize>,
+    show_heatmap: bool,
+    /// practice mode, losing moves need to be confirmed
+    coach: bool,
+    /// move waiting for confirmation and why it loses
+    warning: Option<(usize, String)>,
+    analysis: Option<Analysis>,
+    review: Option<Review>,
+    explorer: Option<Explorer>,
+    /// value of every move of the player, kept up to date while the heatmap is shown
+    move_outcomes: Vec<(usize, Outcome)>,
+    /// value of the position for the player and the depth it was searched to, while playing
+    position_value: Option<(Outcome, i32)>,
+    show_threats: bool,
+    /// cells the player wins, has to block or forks at, kept up to date while shown
+    threats: Vec<(usize, Threat)>,
+    theme: Theme,
+    keys: KeyBindings,
+    symbols: Symbols,
     exit: bool,
 }
 
 impl App {
-    pub fn new() -> Self {
-        App {
-            player: Player::O,
+    pub fn new(config: Config) -> Self {
+        let mut engine = TicTacToe::new(config.size, config.win_length, config.variant);
+        engine.threads = config.threads;
+
+        let mut app = App {
+            player: config.player,
             game_state: GameState::SelectPlayer,
             selected_index: 0,
-            board: TicTacToe::get_empty_board(),
+            board: engine.get_empty_board(),
+            start_board: engine.get_empty_board(),
+            history: vec![],
+            engine,
+            strength: Strength {
+                strategy: config.strategy,
+                max_depth: config.max_depth,
+                think_time: config.think_time,
+                iterations: config.iterations,
+            },
+            starter: config.starter,
+            position: config.position,
+            moves: config.moves,
+            seed: config.seed,
+            seeds: vec![],
+            rng: StdRng::seed_from_u64(0),
+            computer_move: None,
+            search_report: None,
+            show_stats: false,
+            computer_search: None,
+            hint: None,
+            show_heatmap: false,
+            coach: config.coach,
+            warning: None,
+            analysis: None,
+            review: None,
+            explorer: None,
+            move_outcomes: vec![],
+            position_value: None,
+            show_threats: false,
+            threats: vec![],
+            theme: config.theme,
+            keys: config.keys,
+            symbols: config.symbols,
             exit: false,
+        };
+
+        if config.skip_select_player {
+            app.start_game();
         }
+        app
     }
 
     /// runs the application's main loop until the user quits
@@ -58,6 +184,7 @@ impl App {
         while !self.exit {
             terminal.draw(|frame| self.render_ui(frame))?;
             self.handle_events()?;
+            self.check_computer_search();
         }
         Ok(())
     }
@@ -74,29 +201,135 @@ impl App {
             GameState::Playing | GameState::GameOver(_) => {
                 self.render_playing_ui(frame);
             }
+            GameState::Analysis => {
+                self.render_analysis_ui(frame);
+            }
+            GameState::Review => {
+                self.render_review_ui(frame);
+            }
+            GameState::Explorer => {
+                self.render_explorer_ui(frame);
+            }
         }
     }
 
     fn render_container_ui(&self, frame: &mut Frame) {
         let area = frame.area();
 
-        let title = Title::from(" .:: TIC-TAC-TOE ::. ".yellow().bold());
-        let instructions: Title = match self.game_state {
-            GameState::SelectPlayer => Title::from(Line::from(vec![
-                " Press ".into(),
-                "<q>".yellow().bold(),
-                " to Quit ".into(),
-            ])),
-            GameState::Playing | GameState::GameOver(_) => Title::from(Line::from(vec![
-                " Press ".into(),
-                "<q>".yellow().bold(),
-                " to Quit | ".into(),
-                "<r>".yellow().bold(),
-                " to Restart | ".into(),
-                "<s>".yellow().bold(),
-                " to Select Player ".into(),
-            ])),
-        };
+        let title = Title::from(" .:: TIC-TAC-TOE ::. ".fg(self.theme.title).bold());
+        let mut actions = vec![(KeyBindings::get_label(&self.keys.quit), "Quit")];
+        if self.game_state == GameState::Analysis {
+            actions.push((
+                format!(
+                    "{}/{}/{}",
+                    KeyBindings::get_label(&self.keys.place_x),
+                    KeyBindings::get_label(&self.keys.place_o),
+                    KeyBindings::get_label(&self.keys.clear_cell)
+                ),
+                "Place",
+            ));
+            actions.push((KeyBindings::get_label(&self.keys.confirm), "Cycle"));
+            actions.push((
+                KeyBindings::get_label(&self.keys.side_to_move),
+                "Side to Move",
+            ));
+            actions.push((KeyBindings::get_label(&self.keys.import), "Import"));
+            actions.push((KeyBindings::get_label(&self.keys.export), "Export"));
+            actions.push((
+                format!(
+                    "{}/{}",
+                    KeyBindings::get_label(&self.keys.back),
+                    KeyBindings::get_label(&self.keys.analysis)
+                ),
+                "Back",
+            ));
+        } else if self.game_state == GameState::Review {
+            actions.push((
+                format!(
+                    "{}/{}",
+                    KeyBindings::get_label(&self.keys.left),
+                    KeyBindings::get_label(&self.keys.right)
+                ),
+                "Step",
+            ));
+            actions.push((
+                format!(
+                    "{}/{}",
+                    KeyBindings::get_label(&self.keys.up),
+                    KeyBindings::get_label(&self.keys.down)
+                ),
+                "Start/End",
+            ));
+            actions.push((
+                KeyBindings::get_label(&self.keys.losing_move),
+                "Losing Move",
+            ));
+            actions.push((
+                format!(
+                    "{}/{}",
+                    KeyBindings::get_label(&self.keys.back),
+                    KeyBindings::get_label(&self.keys.review)
+                ),
+                "Back",
+            ));
+        } else if self.game_state == GameState::Explorer {
+            actions.push((
+                format!(
+                    "{}/{}",
+                    KeyBindings::get_label(&self.keys.up),
+                    KeyBindings::get_label(&self.keys.down)
+                ),
+                "Select",
+            ));
+            actions.push((
+                format!(
+                    "{}/{}",
+                    KeyBindings::get_label(&self.keys.right),
+                    KeyBindings::get_label(&self.keys.confirm)
+                ),
+                "Expand",
+            ));
+            actions.push((KeyBindings::get_label(&self.keys.left), "Collapse"));
+            actions.push((
+                format!(
+                    "{}/{}",
+                    KeyBindings::get_label(&self.keys.back),
+                    KeyBindings::get_label(&self.keys.explorer)
+                ),
+                "Back",
+            ));
+        } else if self.game_state != GameState::SelectPlayer {
+            actions.push((KeyBindings::get_label(&self.keys.restart), "Restart"));
+            actions.push((
+                KeyBindings::get_label(&self.keys.select_player),
+                "Select Player",
+            ));
+            if self.keys.numpad && self.engine.size == 3 {
+                actions.push(("<1-9>".to_string(), "Play"));
+            }
+            actions.push((KeyBindings::get_label(&self.keys.hint), "Hint"));
+            actions.push((KeyBindings::get_label(&self.keys.heatmap), "Heatmap"));
+            actions.push((KeyBindings::get_label(&self.keys.threats), "Threats"));
+            actions.push((KeyBindings::get_label(&self.keys.stats), "Stats"));
+            actions.push((KeyBindings::get_label(&self.keys.analysis), "Analysis"));
+            if self.game_state == GameState::Playing {
+                actions.push((KeyBindings::get_label(&self.keys.explorer), "Tree"));
+            }
+            if let GameState::GameOver(_) = self.game_state {
+                actions.push((KeyBindings::get_label(&self.keys.review), "Review"));
+            }
+        }
+
+        let mut spans = vec![" ".into()];
+        for (index, (label, action)) in actions.into_iter().enumerate() {
+            if index > 0 {
+                spans.push(" | ".into());
+            }
+            spans.push(label.fg(self.theme.title).bold());
+            spans.push(format!(" {}", action).into());
+        }
+        spans.push(" ".into());
+        let instructions = Title::from(Line::from(spans));
 
         let block = Block::bordered()
             .title(title.alignment(Alignment::Center))
@@ -105,7 +338,8 @@ impl App {
                     .alignment(Alignment::Center)
                     .position(Position::Bottom),
             )
-            .border_set(border::THICK);
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(self.theme.border));
         frame.render_widget(block, area);
     }
 
@@ -126,9 +360,14 @@ impl App {
         frame.render_widget(title, title_area);
 
         let o_player = CellWidget {
-            player: Player::O,
+            cell: Cell::Mark(Player::O),
             is_selected: self.player == Player::O,
             is_winner: false,
+            is_hint: false,
+            outcome: None,
+            threat: None,
+            theme: &self.theme,
+            symbols: &self.symbols,
         };
 
         let o_area = Rect::new(
@@ -140,9 +379,14 @@ impl App {
         frame.render_widget(o_player, o_area);
 
         let x_player = CellWidget {
-            player: Player::X,
+            cell: Cell::Mark(Player::X),
             is_selected: self.player == Player::X,
             is_winner: false,
+            is_hint: false,
+            outcome: None,
+            threat: None,
+            theme: &self.theme,
+            symbols: &self.symbols,
         };
         let x_area = Rect::new(
             (area.width / 2) + 1,
@@ -154,17 +398,9 @@ impl App {
     }
 
     fn render_playing_ui(&self, frame: &mut Frame) {
-        let area = frame.area();
-        let cell_width = CELL_SIZE;
-        let cell_height = CELL_SIZE / 2;
-
         // Select Player
-        let computer = if self.player == Player::O {
-            Player::X
-        } else {
-            Player::O
-        };
-        let title: Text = match &self.game_state {
+        let computer = self.player.get_next();
+        let mut title: Text = match &self.game_state {
             GameState::GameOver(result) => Text::from(Line::from(vec![match result {
                 GameResult::Win(player) => {
                     if player == &self.player {
@@ -177,48 +413,465 @@ impl App {
             }])),
             _ => Text::from(Line::from(vec![
                 "You: ".into(),
-                self.player.get_text().fg(self.player.get_color()).bold(),
-                " | ".fg(Color::DarkGray),
+                self.get_player_span(&self.player),
+                " | ".fg(self.theme.muted),
                 "Computer: ".into(),
-                computer.get_text().fg(computer.get_color()).bold(),
+                self.get_player_span(&computer),
             ])),
         };
+        if self.game_state == GameState::Playing && self.engine.size > 3 {
+            title.push_span(format!(" | {} in a row", self.engine.win_length).fg(self.theme.muted));
+        }
+        if let Some((index, motif)) = self.computer_move {
+            title.push_span(" | ".fg(self.theme.muted));
+            title.push_span(self.get_player_span(&computer));
+            title.push_span(format!(
+                " {} at {}",
+                motif.get_description(),
+                self.engine.get_cell_name(index)
+            ));
+        }
+
+        // Cells
+        let winning_line = match self.game_state {
+            GameState::GameOver(GameResult::Win(_)) => self.engine.get_winning_line(&self.board),
+            _ => None,
+        };
+        let board = BoardWidget {
+            board: &self.board,
+            size: self.engine.size,
+            selected_index: Some(self.selected_index as usize),
+            winning_line,
+            hint: self.hint,
+            move_outcomes: &self.move_outcomes,
+            threats: &self.threats,
+            theme: &self.theme,
+            symbols: &self.symbols,
+        };
+
+        // Seed to replay this game with `--seed`
+        let footer = match (&self.warning, &self.computer_search) {
+            (Some((_, warning)), _) => Some(Text::from(warning.as_str()).fg(self.theme.loss)),
+            (None, Some(search)) => {
+                let frame = search.get_elapsed().as_millis() / SPINNER_INTERVAL.as_millis();
+                let spinner = SPINNER_FRAMES[frame as usize % SPINNER_FRAMES.len()];
+                Some(Text::from(format!("{} Computer is thinking…", spinner)).fg(self.theme.muted))
+            }
+            (None, None) => self
+                .seeds
+                .last()
+                .map(|seed| Text::from(format!("seed {}", seed)).fg(self.theme.muted)),
+        };
+
+        let board_area = self.render_board_ui(frame, frame.area(), title, board, footer);
+        self.render_value_ui(frame, board_area);
+        if self.show_stats {
+            self.render_stats_ui(frame, board_area);
+        }
+    }
+
+    fn render_analysis_ui(&self, frame: &mut Frame) {
+        let Some(analysis) = &self.analysis else {
+            return;
+        };
+
+        let mut spans = vec![
+            "Analysis: ".into(),
+            self.get_player_span(&analysis.player),
+            " to move".into(),
+            " | ".fg(self.theme.muted),
+        ];
+        match (&analysis.result, analysis.outcome) {
+            (GameResult::Win(player), _) => {
+                spans.push(self.get_player_span(player));
+                spans.push(" has won".into());
+            }
+            (GameResult::Draw, _) => spans.push("Draw".into()),
+            (GameResult::Playing, Some(outcome)) => {
+                spans.push(self.get_player_span(&analysis.player));
+                spans.push(" ".into());
+                spans.push(
+                    outcome
+                        .get_description()
+                        .fg(self.theme.get_outcome_color(&outcome)),
+                );
+            }
+            (GameResult::Playing, None) => {}
+        }
+        let title = Text::from(Line::from(spans));
+
+        let board = BoardWidget {
+            board: &analysis.board,
+            size: self.engine.size,
+            selected_index: Some(analysis.selected_index),
+            winning_line: self.engine.get_winning_line(&analysis.board),
+            hint: None,
+            move_outcomes: &analysis.move_outcomes,
+            threats: &[],
+            theme: &self.theme,
+            symbols: &self.symbols,
+        };
+
+        let footer = match (&analysis.input, &analysis.message) {
+            (Some(input), _) => Some(Text::from(format!("import: {}_", input))),
+            (None, Some(message)) => Some(Text::from(message.as_str()).fg(self.theme.muted)),
+            (None, None) => None,
+        };
+
+        self.render_board_ui(frame, frame.area(), title, board, footer);
+    }
+
+    fn render_review_ui(&self, frame: &mut Frame) {
+        let Some(review) = &self.review else {
+            return;
+        };
+
+        let mut spans = vec![format!("Move {}/{}", review.step, review.moves.len()).into()];
+        if let Some(reviewed_move) = review.get_move() {
+            spans.push(" | ".fg(self.theme.muted));
+            spans.push(self.get_player_span(&reviewed_move.player));
+            spans.push(" ".into());
+            spans.push(
+                reviewed_move
+                    .quality
+                    .get_label()
+                    .fg(self.theme.get_quality_color(&reviewed_move.quality))
+                    .bold(),
+            );
+            if reviewed_move.quality != MoveQuality::Best {
+                spans.push(
+                    format!(
+                        " ({} → {})",
+                        reviewed_move.best.get_description(),
+                        reviewed_move.outcome.get_description()
+                    )
+                    .fg(self.theme.muted),
+                );
+            }
+        }
+        let title = Text::from(Line::from(spans));
+
+        // The played move is selected and the best one, when it differs, is hinted
+        let board = review.get_board();
+        let reviewed_move = review.get_move();
+        let board_widget = BoardWidget {
+            board: &board,
+            size: self.engine.size,
+            selected_index: reviewed_move.map(|reviewed_move| reviewed_move.index),
+            winning_line: self.engine.get_winning_line(&board),
+            hint: reviewed_move
+                .filter(|reviewed_move| reviewed_move.quality != MoveQuality::Best)
+                .map(|reviewed_move| reviewed_move.best_index),
+            move_outcomes: &[],
+            threats: &[],
+            theme: &self.theme,
+            symbols: &self.symbols,
+        };
+
+        let computer = self.player.get_next();
+        let get_summary = |player: &Player| {
+            [MoveQuality::Mistake, MoveQuality::Blunder]
+                .map(|quality| match review.count(player, quality) {
+                    1 => format!("1 {}", quality.get_label()),
+                    count => format!("{} {}s", count, quality.get_label()),
+                })
+                .join(", ")
+        };
+        let footer = Text::from(format!(
+            "You: {} | Computer: {}",
+            get_summary(&self.player),
+            get_summary(&computer)
+        ))
+        .fg(self.theme.muted);
+
+        self.render_board_ui(frame, frame.area(), title, board_widget, Some(footer));
+    }
+
+    /// tree of the moves from the position left, board of the selected move right
+    fn render_explorer_ui(&self, frame: &mut Frame) {
+        let Some(explorer) = &self.explorer else {
+            return;
+        };
+        let area = frame.area();
+
+        let tree_area = Rect::new(
+            area.x + 2,
+            area.y + 2,
+            min(36, area.width / 2),
+            area.height.saturating_sub(4),
+        );
+        let block = Block::bordered()
+            .title(" Tree ")
+            .padding(Padding::horizontal(1))
+            .border_style(Style::default().fg(self.theme.cell_border));
+        let inner_area = block.inner(tree_area);
+        frame.render_widget(block, tree_area);
+        let rows = explorer.get_rows();
+        frame.render_widget(
+            TreeWidget {
+                rows: &rows,
+                selected: &explorer.selected,
+                engine: &self.engine,
+                theme: &self.theme,
+                symbols: &self.symbols,
+            },
+            inner_area,
+        );
+
+        // The moves leading to the selected one
+        let variation = explorer.get_variation();
+        let mut spans = vec![];
+        for (position, node) in variation.iter().enumerate() {
+            if position > 0 {
+                spans.push(" → ".fg(self.theme.muted));
+            }
+            spans.push(self.get_player_span(&node.player));
+            spans.push(format!(" {}", self.engine.get_cell_name(node.index)).into());
+        }
+        if spans.is_empty() {
+            spans.push(self.get_player_span(&explorer.player));
+            spans.push(" has no moves left".into());
+        }
+        let title = Text::from(Line::from(spans));
+
+        // Replies of an expanded move are labelled on their cells
+        let board = explorer.get_board();
+        let selected = variation.last();
+        let move_outcomes: Vec<(usize, Outcome)> = selected
+            .filter(|node| node.is_expanded)
+            .and_then(|node| node.children.as_ref())
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|child| (child.index, child.outcome))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let board_widget = BoardWidget {
+            board: &board,
+            size: self.engine.size,
+            selected_index: selected.map(|node| node.index),
+            winning_line: self.engine.get_winning_line(&board),
+            hint: None,
+            move_outcomes: &move_outcomes,
+            threats: &[],
+            theme: &self.theme,
+            symbols: &self.symbols,
+        };
+
+        let footer = selected.map(|node| {
+            let best = if node.is_best { ", a best move" } else { "" };
+            Text::from(format!(
+                "{} {}, score {}{}",
+                self.engine.get_cell_name(node.index),
+                node.outcome.get_description(),
+                node.score,
+                best
+            ))
+            .fg(self.theme.muted)
+        });
+
+        let board_area = Rect::new(
+            tree_area.right(),
+            area.y,
+            area.width.saturating_sub(tree_area.right()),
+            area.height,
+        );
+        self.render_board_ui(frame, board_area, title, board_widget, footer);
+    }
+
+    /// title, board and an optional footer line centred in `area`
+    /// returns the area of the board
+    fn render_board_ui(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        title: Text,
+        board: BoardWidget,
+        footer: Option<Text>,
+    ) -> Rect {
+        let size = self.engine.size;
+        let (cell_width, cell_height) = self.get_cell_size(area);
+        let total_width = cell_width * size;
+        let total_height = cell_height * size;
 
         let title_area = Rect::new(
-            (area.width / 2) - ((title.width() as u16) / 2),
-            (area.height / 2) - 8,
+            area.x + (area.width / 2).saturating_sub((title.width() as u16) / 2),
+            area.y + (area.height / 2).saturating_sub((total_height + 2) / 2),
             title.width() as u16,
             title.height() as u16,
         );
-
         frame.render_widget(title, title_area);
 
-        // Cells
-        let total_width = cell_width * BOARD_SIZE;
-        let margin_left = (area.width / 2) - (total_width / 2);
-        let margin_top = title_area.y + 2;
-
-        for row in 0..BOARD_SIZE {
-            for col in 0..BOARD_SIZE {
-                let index: u16 = (row * BOARD_SIZE) + col;
-                let cell = CellWidget {
-                    player: self.board[index as usize].clone(),
-                    is_selected: index == self.selected_index,
-                    is_winner: false,
-                };
+        let board_area = Rect::new(
+            area.x + (area.width / 2).saturating_sub(total_width / 2),
+            title_area.y + 2,
+            total_width,
+            total_height,
+        );
+        frame.render_widget(board, board_area);
 
-                let cell_area = Rect::new(
-                    margin_left + (col * cell_width),
-                    margin_top + (row * cell_height),
-                    cell_width,
-                    cell_height,
+        if let Some(footer) = footer {
+            let footer_y = board_area.y + total_height;
+            if footer_y + 1 < area.bottom() {
+                let footer_width = min(footer.width() as u16, area.width.saturating_sub(2));
+                let footer_area = Rect::new(
+                    area.x + (area.width / 2).saturating_sub(footer_width / 2),
+                    footer_y,
+                    footer_width,
+                    1,
                 );
-                frame.render_widget(cell, cell_area);
+                frame.render_widget(footer, footer_area);
             }
         }
+
+        board_area
+    }
+
+    /// value of the position for the player, right of the board when there is room for it
+    fn render_value_ui(&self, frame: &mut Frame, board_area: Rect) {
+        let Some((outcome, max_depth)) = self.position_value else {
+            return;
+        };
+
+        let area = frame.area();
+        let height = if self.search_report.is_some() { 6 } else { 5 };
+        let panel_area = Rect::new(board_area.right() + 2, board_area.y, 30, height);
+        if panel_area.right() + 1 > area.right() || panel_area.bottom() + 1 > area.bottom() {
+            return;
+        }
+
+        let depth = if max_depth == -1 {
+            "solved".to_string()
+        } else {
+            format!("depth {}", max_depth)
+        };
+        let mut text = Text::from(vec![
+            Line::from(vec![self.get_player_span(&self.player), " to move".into()]),
+            Line::from(vec![
+                self.get_player_span(&self.player),
+                " ".into(),
+                outcome
+                    .get_description()
+                    .fg(self.theme.get_outcome_color(&outcome)),
+            ]),
+            Line::from(depth.fg(self.theme.muted)),
+        ]);
+        // How far the computer saw in its time for the last move
+        if let Some(report) = &self.search_report {
+            let search = match (report.depth, report.nodes) {
+                (-1, 0) => " from the solved table".to_string(),
+                (-1, nodes) => format!(" solved, {} nodes", nodes),
+                (depth, nodes) if self.strength.strategy == Strategy::Mcts => {
+                    format!(" depth {}, {} playouts", depth, nodes)
+                }
+                (depth, nodes) => format!(" depth {}, {} nodes", depth, nodes),
+            };
+            text.push_line(Line::from(vec![
+                self.get_player_span(&self.player.get_next()),
+                search.fg(self.theme.muted),
+            ]));
+        }
+
+        let block = Block::bordered()
+            .title(" Value ")
+            .padding(Padding::horizontal(1))
+            .border_style(Style::default().fg(self.theme.cell_border));
+        let inner_area = block.inner(panel_area);
+        frame.render_widget(block, panel_area);
+        frame.render_widget(text, inner_area);
+    }
+
+    /// what the search of the computer's last move did, left of the board when there is room
+    fn render_stats_ui(&self, frame: &mut Frame, board_area: Rect) {
+        let width = 30;
+        let mut text = Text::default();
+        match &self.search_report {
+            Some(report) => {
+                let depth = match (report.depth, report.nodes) {
+                    (-1, 0) => "from the solved table".to_string(),
+                    (-1, _) => "solved".to_string(),
+                    (depth, _) => format!("depth {}", depth),
+                };
+                let nodes = if self.strength.strategy == Strategy::Mcts {
+                    "playouts"
+                } else {
+                    "nodes"
+                };
+                text.push_line(depth);
+                text.push_line(format!("{} {}", report.nodes, nodes));
+                text.push_line(format!("{} table hits", report.table_hits));
+                text.push_line(format!("{:.1} ms", report.elapsed.as_secs_f64() * 1000.0));
+
+                // The variation wraps between cells, a few lines at most
+                let mut lines = vec![String::new()];
+                for index in &report.principal_variation {
+                    let name = self.engine.get_cell_name(*index);
+                    let line = lines.last_mut().unwrap();
+                    if line.is_empty() {
+                        line.push_str("pv");
+                    }
+                    if line.len() + name.len() + 1 > (width - 4) as usize {
+                        lines.push(String::new());
+                    }
+                    let line = lines.last_mut().unwrap();
+                    line.push(' ');
+                    line.push_str(&name);
+                }
+                for line in lines.into_iter().take(4) {
+                    text.push_line(line.fg(self.theme.muted));
+                }
+            }
+            None => text.push_line("no search yet".fg(self.theme.muted)),
+        }
+
+        let area = frame.area();
+        let height = text.height() as u16 + 2;
+        let Some(x) = board_area.x.checked_sub(width + 2) else {
+            return;
+        };
+        let panel_area = Rect::new(x, board_area.y, width, height);
+        if x < 1 || panel_area.bottom() + 1 > area.bottom() {
+            return;
+        }
+
+        let block = Block::bordered()
+            .title(" Search ")
+            .padding(Padding::horizontal(1))
+            .border_style(Style::default().fg(self.theme.cell_border));
+        let inner_area = block.inner(panel_area);
+        frame.render_widget(block, panel_area);
+        frame.render_widget(text, inner_area);
+    }
+
+    fn get_player_span(&self, player: &Player) -> Span<'_> {
+        self.symbols
+            .get_player_symbol(player)
+            .fg(self.theme.get_player_color(player))
+            .bold()
+    }
+
+    /// biggest cell that fits the board in the frame, bordered cells are at least 3 rows high
+    /// and fall back to a single row without borders
+    fn get_cell_size(&self, area: Rect) -> (u16, u16) {
+        let size = self.engine.size;
+        let max_height = area.height.saturating_sub(8) / size;
+        let max_width = area.width.saturating_sub(4) / size;
+
+        let cell_height = min(CELL_SIZE / 2, min(max_height, max_width / 2));
+        if cell_height >= 3 {
+            (cell_height * 2, cell_height)
+        } else {
+            (3, 1)
+        }
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
+        // Wake up regularly while the computer thinks, to turn the spinner and pick up its move
+        if self.computer_search.is_some() && !event::poll(SPINNER_INTERVAL)? {
+            return Ok(());
+        }
+
         match event::read()? {
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
@@ -231,22 +884,70 @@ impl App {
     }
 
     fn handle_key_event(&mut self, key_event: KeyEvent) {
-        if key_event.code == KeyCode::Char('q') {
+        if self.game_state == GameState::Analysis && self.handle_analysis_key_event(key_event) {
+            return;
+        }
+
+        let Some(action) = self.keys.get_action(key_event.code) else {
+            return;
+        };
+
+        if action == Action::Quit {
             self.exit = true;
             return;
         }
 
+        // The board isn't the player's while the computer thinks, only moving around, the search
+        // statistics and leaving the game work
+        if self.computer_search.is_some()
+            && !matches!(
+                action,
+                Action::Restart
+                    | Action::SelectPlayer
+                    | Action::Left
+                    | Action::Right
+                    | Action::Up
+                    | Action::Down
+                    | Action::Stats
+            )
+        {
+            return;
+        }
+
         match self.game_state {
-            GameState::Playing | GameState::GameOver(_) => match key_event.code {
-                KeyCode::Char('s') => {
+            GameState::Playing | GameState::GameOver(_) => match action {
+                Action::SelectPlayer => {
+                    self.computer_search = None;
                     self.game_state = GameState::SelectPlayer;
                     self.selected_index = 0;
-                    self.board = TicTacToe::get_empty_board();
+                    self.board = self.engine.get_empty_board();
+                    self.hint = None;
+                    self.position_value = None;
+                    self.move_outcomes = vec![];
+                    self.threats = vec![];
                 }
-                KeyCode::Char('r') => {
-                    self.game_state = GameState::Playing;
-                    self.selected_index = 0;
-                    self.board = TicTacToe::get_empty_board();
+                Action::Restart => self.start_game(),
+                Action::Heatmap => {
+                    self.show_heatmap = !self.show_heatmap;
+                    self.update_overlays();
+                }
+                Action::Threats => {
+                    self.show_threats = !self.show_threats;
+                    self.update_overlays();
+                }
+                Action::Stats => self.show_stats = !self.show_stats,
+                Action::Analysis => {
+                    // Return so the same key doesn't close the screen it just opened
+                    self.open_analysis();
+                    return;
+                }
+                Action::Review if self.game_state != GameState::Playing => {
+                    self.open_review();
+                    return;
+                }
+                Action::Explorer if self.game_state == GameState::Playing => {
+                    self.open_explorer();
+                    return;
                 }
                 _ => {}
             },
@@ -254,87 +955,395 @@ impl App {
         }
 
         match self.game_state {
-            GameState::SelectPlayer => match key_event.code {
-                KeyCode::Left => self.player = Player::O,
-                KeyCode::Right => self.player = Player::X,
-                KeyCode::Enter => {
-                    self.game_state = GameState::Playing;
+            GameState::SelectPlayer => match action {
+                Action::Left => self.player = Player::O,
+                Action::Right => self.player = Player::X,
+                Action::Confirm => self.start_game(),
+                _ => {}
+            },
+            GameState::Playing => match action {
+                Action::Left | Action::Right | Action::Up | Action::Down => {
+                    self.selected_index = App::get_moved_index(
+                        self.engine.size,
+                        self.selected_index as usize,
+                        action,
+                    ) as u16;
+                    self.warning = None;
+                }
+                Action::Confirm => self.play_as_human(),
+                Action::Hint => self.show_hint(),
+                // Numpad cells only exist on a 3x3 board
+                Action::Cell(index) if self.engine.size == 3 => {
+                    self.selected_index = index;
+                    self.play_as_human();
                 }
                 _ => {}
             },
-            GameState::Playing => match key_event.code {
-                // 0 1 2
-                // 3 4 5
-                // 6 7 8
-                KeyCode::Left => {
-                    self.selected_index = match self.selected_index {
-                        0..3 => max(0, self.selected_index.saturating_sub(1)),
-                        3..6 => max(3, self.selected_index.saturating_sub(1)),
-                        6..9 => max(6, self.selected_index.saturating_sub(1)),
-                        _ => self.selected_index,
+            GameState::GameOver(_) => {}
+            GameState::Analysis => match action {
+                Action::Left | Action::Right | Action::Up | Action::Down => {
+                    if let Some(analysis) = &mut self.analysis {
+                        analysis.selected_index =
+                            App::get_moved_index(self.engine.size, analysis.selected_index, action);
                     }
                 }
-                KeyCode::Right => {
-                    self.selected_index = match self.selected_index {
-                        0..3 => min(2, self.selected_index + 1),
-                        3..6 => min(5, self.selected_index + 1),
-                        6..9 => min(8, self.selected_index + 1),
-                        _ => self.selected_index,
+                Action::Confirm => {
+                    if let Some(analysis) = &mut self.analysis {
+                        analysis.cycle_cell(&self.engine);
                     }
                 }
-                KeyCode::Up => {
-                    self.selected_index = match self.selected_index % BOARD_SIZE {
-                        0 => max(0, self.selected_index.saturating_sub(BOARD_SIZE)),
-                        1 => max(1, self.selected_index.saturating_sub(BOARD_SIZE)),
-                        2 => max(2, self.selected_index.saturating_sub(BOARD_SIZE)),
-                        _ => self.selected_index,
+                Action::PlaceX | Action::PlaceO | Action::ClearCell => {
+                    let cell = match action {
+                        Action::PlaceX => Cell::Mark(Player::X),
+                        Action::PlaceO => Cell::Mark(Player::O),
+                        _ => Cell::Empty,
+                    };
+                    if let Some(analysis) = &mut self.analysis {
+                        analysis.set_cell(&self.engine, cell);
                     }
                 }
-                KeyCode::Down => {
-                    self.selected_index = match self.selected_index % BOARD_SIZE {
-                        0 => min(6, self.selected_index + BOARD_SIZE),
-                        1 => min(7, self.selected_index + BOARD_SIZE),
-                        2 => min(8, self.selected_index + BOARD_SIZE),
-                        _ => self.selected_index,
+                Action::SideToMove => {
+                    if let Some(analysis) = &mut self.analysis {
+                        analysis.toggle_player(&self.engine);
                     }
                 }
-
-                KeyCode::Enter => {
-                    if self.board[self.selected_index as usize] == Player::None {
-                        self.board[self.selected_index as usize] = self.player.clone();
-
-                        self.play_as_computer();
-                        self.check_game_state();
+                Action::Import => {
+                    if let Some(analysis) = &mut self.analysis {
+                        analysis.input = Some(String::new());
+                        analysis.message = None;
                     }
                 }
-
+                Action::Export => {
+                    if let Some(analysis) = &mut self.analysis {
+                        analysis.export(&self.engine);
+                    }
+                }
+                Action::Analysis | Action::Back => self.return_to_game(),
                 _ => {}
             },
-            GameState::GameOver(_) => {}
+            GameState::Review => {
+                if let Some(review) = &mut self.review {
+                    match action {
+                        Action::Left => review.step_back(),
+                        Action::Right => review.step_forward(),
+                        Action::Up => review.step = 0,
+                        Action::Down => review.step = review.moves.len(),
+                        Action::LosingMove => review.jump_to_losing_move(),
+                        _ => {}
+                    }
+                }
+                if matches!(action, Action::Review | Action::Back) {
+                    self.return_to_game();
+                }
+            }
+            GameState::Explorer => {
+                if let Some(explorer) = &mut self.explorer {
+                    match action {
+                        Action::Up => explorer.select_previous(),
+                        Action::Down => explorer.select_next(),
+                        Action::Right | Action::Confirm => explorer.expand(&self.engine),
+                        Action::Left => explorer.collapse(),
+                        _ => {}
+                    }
+                }
+                if matches!(action, Action::Explorer | Action::Back) {
+                    self.return_to_game();
+                }
+            }
+        }
+    }
+
+    fn get_moved_index(size: u16, index: usize, action: Action) -> usize {
+        let size = size as usize;
+        let (row, col) = (index / size, index % size);
+
+        match action {
+            Action::Left if col > 0 => index - 1,
+            Action::Right if col + 1 < size => index + 1,
+            Action::Up if row > 0 => index - size,
+            Action::Down if row + 1 < size => index + size,
+            _ => index,
+        }
+    }
+
+    /// typing of a position string to import, a text field rather than bindings, `true` when the
+    /// key was consumed
+    fn handle_analysis_key_event(&mut self, key_event: KeyEvent) -> bool {
+        let Some(input) = self
+            .analysis
+            .as_mut()
+            .and_then(|analysis| analysis.input.as_mut())
+        else {
+            return false;
+        };
+
+        match key_event.code {
+            KeyCode::Char(char) => input.push(char),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                if let Some(analysis) = &mut self.analysis {
+                    analysis.import(&self.engine);
+                }
+            }
+            KeyCode::Esc => {
+                if let Some(analysis) = &mut self.analysis {
+                    analysis.input = None;
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn open_analysis(&mut self) {
+        // Once the game is over it's the turn of whoever didn't make the last move
+        let count = |player: &Player| {
+            self.board
+                .iter()
+                .filter(|cell| cell.is_marked_by(player))
+                .count()
+        };
+        let player = match self.engine.get_winning_line(&self.board) {
+            Some(line) => self.board[line[0]]
+                .get_player()
+                .map_or(self.player.clone(), Player::get_next),
+            None if count(&self.player) > count(&self.player.get_next()) => self.player.get_next(),
+            None => self.player.clone(),
+        };
+
+        self.analysis = Some(Analysis::new(
+            &self.engine,
+            self.board.clone(),
+            player,
+            self.selected_index as usize,
+        ));
+        self.game_state = GameState::Analysis;
+    }
+
+    fn open_review(&mut self) {
+        self.review = Some(Review::new(
+            &self.engine,
+            self.start_board.clone(),
+            &self.history,
+        ));
+        self.game_state = GameState::Review;
+    }
+
+    fn open_explorer(&mut self) {
+        self.explorer = Some(Explorer::new(
+            &self.engine,
+            self.board.clone(),
+            self.player.clone(),
+            self.strength.max_depth,
+        ));
+        self.game_state = GameState::Explorer;
+    }
+
+    /// back to the game from analysis, review or the tree explorer, the game is left as it was
+    fn return_to_game(&mut self) {
+        self.analysis = None;
+        self.review = None;
+        self.explorer = None;
+        self.game_state = GameState::Playing;
+        self.check_game_state();
+        self.update_overlays();
+    }
+
+    fn start_game(&mut self) {
+        self.computer_search = None;
+        self.game_state = GameState::Playing;
+        self.selected_index = 0;
+        self.board = self.engine.get_empty_board();
+
+        let seed = self.seed.take().unwrap_or_else(|| rand::thread_rng().gen());
+        self.seeds.push(seed);
+        self.rng = StdRng::seed_from_u64(seed);
+
+        let mut player = if let Some(position) = &self.position {
+            self.board = position.board.clone();
+            position.player.clone()
+        } else {
+            let is_computer_first = match self.starter {
+                Starter::Human => false,
+                Starter::Computer => true,
+                Starter::Random => self.rng.gen_bool(0.5),
+            };
+
+            if is_computer_first {
+                self.player.get_next()
+            } else {
+                self.player.clone()
+            }
+        };
+        for &index in &self.moves {
+            self.board[index] = Cell::Mark(player.clone());
+            player = player.get_next();
+        }
+        self.start_board = self.board.clone();
+        self.history = vec![];
+        self.warning = None;
+        self.computer_move = None;
+        self.search_report = None;
+
+        if player != self.player {
+            self.play_as_computer();
+        }
+        self.check_game_state();
+        self.hint = None;
+        self.update_overlays();
+    }
+
+    fn play_as_human(&mut self) {
+        let index = self.selected_index as usize;
+        if !self.board[index].is_empty() {
+            return;
+        }
+
+        // A warned move is played when it's confirmed
+        let is_confirmed = self
+            .warning
+            .take()
+            .is_some_and(|(warned_index, _)| warned_index == index);
+        if self.coach && !is_confirmed {
+            self.warning = self
+                .get_losing_move_warning(index)
+                .map(|warning| (index, warning));
+            if self.warning.is_some() {
+                return;
+            }
+        }
+
+        self.board[self.selected_index as usize] = Cell::Mark(self.player.clone());
+        self.history
+            .push((self.selected_index as usize, self.player.clone()));
+        self.hint = None;
+
+        self.play_as_computer();
+        self.check_game_state();
+        self.update_overlays();
+    }
+
+    /// explains how the computer wins after `index`, when it loses a drawn or won game
+    fn get_losing_move_warning(&self, index: usize) -> Option<String> {
+        let (move_outcomes, _) = self.engine.get_move_outcomes(&self.board, &self.player, -1);
+        let (_, _, best) = move_outcomes.first()?;
+        let (_, _, outcome) = move_outcomes
+            .iter()
+            .find(|(move_index, _, _)| *move_index == index)?;
+
+        let quality = MoveQuality::from_outcomes(*best, *outcome);
+        if quality != MoveQuality::Blunder {
+            return None;
+        }
+
+        // The computer's best reply is the start of the threat
+        let mut board = self.board.clone();
+        board[index] = Cell::Mark(self.player.clone());
+        let computer = self.player.get_next();
+        let (reply_outcomes, _) = self.engine.get_move_outcomes(&board, &computer, -1);
+        let (reply, _, best_reply) = reply_outcomes.first()?;
+
+        let threat = match best_reply {
+            Outcome::Win(1) => format!("wins at {}", self.engine.get_cell_name(*reply)),
+            Outcome::Win(moves) => format!(
+                "forces a win in {} moves starting at {}",
+                moves,
+                self.engine.get_cell_name(*reply)
+            ),
+            _ => format!(
+                "gets the upper hand at {}",
+                self.engine.get_cell_name(*reply)
+            ),
+        };
+        Some(format!(
+            "{} loses, the computer {} | {} to play it anyway",
+            self.engine.get_cell_name(index),
+            threat,
+            KeyBindings::get_label(&self.keys.confirm)
+        ))
+    }
+
+    /// selects the best move, the first one of equally good moves so hints don't consume the game's randomness
+    fn show_hint(&mut self) {
+        let (move_outcomes, _) = self.engine.get_move_outcomes(&self.board, &self.player, -1);
+        if let Some((index, _, _)) = move_outcomes.first() {
+            self.hint = Some(*index);
+            self.selected_index = *index as u16;
         }
     }
 
+    /// value, heatmap & threats of the player, empty when hidden, while the computer thinks or once
+    /// the game is over
+    fn update_overlays(&mut self) {
+        self.position_value = None;
+        self.move_outcomes = vec![];
+        self.threats = vec![];
+        if self.game_state != GameState::Playing || self.computer_search.is_some() {
+            return;
+        }
+
+        if self.show_threats {
+            self.threats = self.engine.get_threats(&self.board, &self.player);
+        }
+
+        let (move_outcomes, max_depth) =
+            self.engine.get_move_outcomes(&self.board, &self.player, -1);
+        self.position_value = move_outcomes
+            .first()
+            .map(|(_, _, outcome)| (*outcome, max_depth));
+        if self.show_heatmap {
+            self.move_outcomes = move_outcomes
+                .into_iter()
+                .map(|(index, _, outcome)| (index, outcome))
+                .collect();
+        }
+    }
+
+    /// starts searching the computer's move, played by `check_computer_search`
     fn play_as_computer(&mut self) {
-        if TicTacToe::is_full(&self.board) {
+        if self.engine.get_game_result(&self.board) != GameResult::Playing {
             return;
         }
 
-        let mut nodes_map: HashMap<i32, Vec<i32>> = HashMap::new();
-        let index = TicTacToe::get_best_move(
-            &self.board,
-            self.player.get_opponent(),
-            true,
-            0,
-            &mut nodes_map,
-        ) as usize;
+        // Seeded by the game's generator so a replayed game makes the same choices
+        let rng = StdRng::seed_from_u64(self.rng.gen());
+        self.computer_search = Some(ComputerSearch::start(
+            self.engine.clone(),
+            self.board.clone(),
+            self.player.get_next(),
+            self.strength,
+            rng,
+        ));
+    }
+
+    /// plays the computer's move once its search is over
+    fn check_computer_search(&mut self) {
+        let Some(computer_move) = self
+            .computer_search
+            .as_ref()
+            .and_then(ComputerSearch::try_get_move)
+        else {
+            return;
+        };
+        self.computer_search = None;
 
-        if index <= self.board.len() {
-            self.board[index] = self.player.get_opponent();
+        if let Some((index, report)) = computer_move {
+            let motif = self
+                .engine
+                .get_motif(&self.board, index, &self.player.get_next());
+            self.computer_move = Some((index, motif));
+            self.search_report = Some(report);
+            self.board[index] = Cell::Mark(self.player.get_next());
+            self.history.push((index, self.player.get_next()));
         }
+        self.check_game_state();
+        self.update_overlays();
     }
 
     fn check_game_state(&mut self) {
-        let result = TicTacToe::get_game_result(&self.board);
+        let result = self.engine.get_game_result(&self.board);
         if result != GameResult::Playing {
             self.game_state = GameState::GameOver(result);
         }
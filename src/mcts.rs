@@ -0,0 +1,240 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    bitboard::Mask,
+    entities::{Board, Cell, GameResult, Player, SearchReport},
+    tictactoe::TicTacToe,
+};
+
+/// exploration constant of UCT, the usual √2
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// position reached by a move from its parent
+#[derive(Debug)]
+struct Node {
+    /// cell played to reach the node, `None` for the root
+    index: Option<usize>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// moves that have no child yet
+    untried: Mask,
+    /// number of moves to the node from the root
+    depth: i32,
+    result: GameResult,
+    visits: u32,
+    /// total reward of the playouts through the node, for the player who moved into it
+    reward: f64,
+}
+
+impl Node {
+    fn new(index: Option<usize>, parent: Option<usize>, depth: i32, board: &Board) -> Self {
+        Node {
+            index,
+            parent,
+            children: vec![],
+            untried: Mask::from_cells(TicTacToe::get_available_moves(board)),
+            depth,
+            result: GameResult::Playing,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    /// upper confidence bound of the node, seen from the player who moved into it
+    fn get_bound(&self, parent_visits: u32) -> f64 {
+        self.reward / self.visits as f64
+            + EXPLORATION * ((parent_visits as f64).ln() / self.visits as f64).sqrt()
+    }
+}
+
+/// tree of a Monte Carlo search, nodes index into `nodes`, the root being the first
+#[derive(Debug)]
+pub struct MonteCarlo<'a> {
+    engine: &'a TicTacToe,
+    board: Board,
+    player: Player,
+    nodes: Vec<Node>,
+}
+
+impl<'a> MonteCarlo<'a> {
+    /// tree of `player`'s moves from `board`, where the game is still being played
+    pub fn new(engine: &'a TicTacToe, board: &Board, player: Player) -> Self {
+        MonteCarlo {
+            engine,
+            board: board.clone(),
+            player,
+            nodes: vec![Node::new(None, None, 0, board)],
+        }
+    }
+
+    /// most visited move after `iterations` playouts, or as many as fit in `think_time`, with
+    /// the depth of the tree and the number of playouts. `None` once `cancel` is set
+    pub fn search(
+        &mut self,
+        iterations: u32,
+        think_time: Option<Duration>,
+        cancel: &AtomicBool,
+        rng: &mut impl Rng,
+    ) -> Option<(usize, SearchReport)> {
+        let started = Instant::now();
+        let deadline = think_time.map(|think_time| started + think_time);
+        let mut playouts = 0;
+
+        loop {
+            self.iterate(rng);
+            playouts += 1;
+
+            // The clock and the cancel flag are only checked every so often
+            if playouts % 64 == 0 && cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let is_over = match deadline {
+                Some(deadline) => playouts % 64 == 0 && Instant::now() >= deadline,
+                None => playouts >= iterations,
+            };
+            if is_over {
+                break;
+            }
+        }
+
+        // The most visited move of every node, from the root down
+        let mut principal_variation = vec![];
+        let mut node = 0;
+        while let Some(&child) = self.nodes[node]
+            .children
+            .iter()
+            .max_by_key(|&&child| self.nodes[child].visits)
+        {
+            principal_variation.extend(self.nodes[child].index);
+            node = child;
+        }
+
+        let report = SearchReport {
+            depth: self.nodes.iter().map(|node| node.depth).max().unwrap_or(0),
+            nodes: playouts as u64,
+            elapsed: started.elapsed(),
+            principal_variation,
+            ..SearchReport::default()
+        };
+        let index = *report.principal_variation.first()?;
+        Some((index, report))
+    }
+
+    /// one playout: walks down the tree by UCT, adds a node and plays randomly to the end
+    fn iterate(&mut self, rng: &mut impl Rng) {
+        let mut board = self.board.clone();
+        let mut player = self.player.clone();
+        let mut node = 0;
+
+        // Selection
+        while self.nodes[node].untried.is_empty() && self.nodes[node].result == GameResult::Playing
+        {
+            let parent_visits = self.nodes[node].visits;
+            let Some(&child) = self.nodes[node].children.iter().max_by(|&&a, &&b| {
+                let a = self.nodes[a].get_bound(parent_visits);
+                let b = self.nodes[b].get_bound(parent_visits);
+                a.total_cmp(&b)
+            }) else {
+                break;
+            };
+            node = child;
+            if let Some(index) = self.nodes[node].index {
+                board[index] = Cell::Mark(player.clone());
+            }
+            player = player.get_next();
+        }
+
+        // Expansion
+        if self.nodes[node].result == GameResult::Playing {
+            let untried = self.nodes[node].untried;
+            if let Some(index) = untried
+                .get_cells()
+                .nth(rng.gen_range(0..untried.get_count()))
+            {
+                self.nodes[node].untried.clear(index);
+                board[index] = Cell::Mark(player.clone());
+                let mut child =
+                    Node::new(Some(index), Some(node), self.nodes[node].depth + 1, &board);
+                child.result = self.engine.get_move_result(&board, index);
+                if child.result != GameResult::Playing {
+                    child.untried = Mask::default();
+                }
+                self.nodes.push(child);
+                let child = self.nodes.len() - 1;
+                self.nodes[node].children.push(child);
+                node = child;
+                player = player.get_next();
+            }
+        }
+
+        // Simulation
+        let mut mover = player.get_next();
+        let mut result = self.nodes[node].result.clone();
+        if result == GameResult::Playing {
+            let mut moves = TicTacToe::get_available_moves(&board);
+            moves.shuffle(rng);
+            for index in moves {
+                board[index] = Cell::Mark(player.clone());
+                result = self.engine.get_move_result(&board, index);
+                if result != GameResult::Playing {
+                    break;
+                }
+                player = player.get_next();
+            }
+        }
+
+        // Backpropagation, each node scored for the player who moved into it
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.reward += match &result {
+                GameResult::Win(winner) if *winner == mover => 1.0,
+                GameResult::Win(_) => 0.0,
+                _ => 0.5,
+            };
+            mover = mover.get_next();
+            current = node.parent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::entities::Variant;
+
+    fn get_move(position: &str, variant: Variant) -> usize {
+        let parsed = TicTacToe::parse_position(position).unwrap();
+        let engine = TicTacToe::new(parsed.size, parsed.win_length, variant);
+        let mut rng = StdRng::seed_from_u64(0);
+        let (index, _) = MonteCarlo::new(&engine, &parsed.board, parsed.player)
+            .search(500, None, &AtomicBool::new(false), &mut rng)
+            .unwrap();
+        index
+    }
+
+    #[test]
+    fn takes_the_win() {
+        assert_eq!(get_move("xx./oo./... x 3 3", Variant::Standard), 2);
+    }
+
+    #[test]
+    fn blocks_the_loss() {
+        assert_eq!(get_move("xx./o../... o 3 3", Variant::Standard), 2);
+    }
+
+    #[test]
+    fn avoids_completing_a_line_in_misere() {
+        // Completing the top row loses, and taking the middle row's last cell saves O
+        let index = get_move("xx./oo./... x 3 3", Variant::Misere);
+        assert!(![2, 5].contains(&index), "{}", index);
+    }
+}
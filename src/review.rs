@@ -0,0 +1,103 @@
+use crate::{
+    entities::{Board, Cell, MoveQuality, Outcome, Player},
+    tictactoe::TicTacToe,
+};
+
+/// a move of the finished game, scored against the best move available at that point
+#[derive(Debug)]
+pub struct ReviewedMove {
+    pub index: usize,
+    pub player: Player,
+    /// first of the best moves
+    pub best_index: usize,
+    pub best: Outcome,
+    pub outcome: Outcome,
+    pub quality: MoveQuality,
+}
+
+/// replay of a finished game, stepping through its moves
+#[derive(Debug)]
+pub struct Review {
+    pub start_board: Board,
+    pub moves: Vec<ReviewedMove>,
+    /// number of moves played on the shown board
+    pub step: usize,
+}
+
+impl Review {
+    /// evaluates every move of `history`, played in order from `start_board`
+    pub fn new(engine: &TicTacToe, start_board: Board, history: &[(usize, Player)]) -> Self {
+        let mut board = start_board.clone();
+        let mut moves = vec![];
+
+        for (index, player) in history {
+            let (move_outcomes, max_depth) = engine.get_move_outcomes(&board, player, -1);
+
+            let (best_index, best) = move_outcomes.first().map_or(
+                (*index, Outcome::from_score(0, max_depth == -1)),
+                |(index, _, outcome)| (*index, *outcome),
+            );
+            let outcome = move_outcomes
+                .iter()
+                .find(|(move_index, _, _)| move_index == index)
+                .map_or(best, |(_, _, outcome)| *outcome);
+            moves.push(ReviewedMove {
+                index: *index,
+                player: player.clone(),
+                best_index,
+                best,
+                outcome,
+                quality: MoveQuality::from_outcomes(best, outcome),
+            });
+
+            board[*index] = Cell::Mark(player.clone());
+        }
+
+        Review {
+            start_board,
+            step: moves.len(),
+            moves,
+        }
+    }
+
+    pub fn get_board(&self) -> Board {
+        let mut board = self.start_board.clone();
+        for reviewed_move in &self.moves[..self.step] {
+            board[reviewed_move.index] = Cell::Mark(reviewed_move.player.clone());
+        }
+        board
+    }
+
+    /// last move shown on the board
+    pub fn get_move(&self) -> Option<&ReviewedMove> {
+        self.step.checked_sub(1).map(|index| &self.moves[index])
+    }
+
+    pub fn step_back(&mut self) {
+        self.step = self.step.saturating_sub(1);
+    }
+
+    pub fn step_forward(&mut self) {
+        self.step = (self.step + 1).min(self.moves.len());
+    }
+
+    /// steps to the last blunder, after which the game was lost for good
+    pub fn jump_to_losing_move(&mut self) {
+        if let Some(index) = self
+            .moves
+            .iter()
+            .rposition(|reviewed_move| reviewed_move.quality == MoveQuality::Blunder)
+        {
+            self.step = index + 1;
+        }
+    }
+
+    pub fn count(&self, player: &Player, quality: MoveQuality) -> usize {
+        self.moves
+            .iter()
+            .filter(|reviewed_move| {
+                &reviewed_move.player == player && reviewed_move.quality == quality
+            })
+            .count()
+    }
+}
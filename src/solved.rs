@@ -0,0 +1,153 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use crate::{
+    entities::{Board, Cell, GameResult, Player, Variant},
+    symmetry::Symmetries,
+    tictactoe::TicTacToe,
+};
+
+// Every position of the 3x3 game that is still being played, reduced by symmetry and with the
+// side to move as X. One section per variant, standard then misère, each a `u16` count followed
+// by 5-byte entries sorted by position code: code (`u16`), score (`i8`), best moves (`u16` mask).
+// Regenerate with `tictactoe-tui --generate-solved-table data/solved_3x3.bin`.
+const TABLE: &[u8] = include_bytes!("../data/solved_3x3.bin");
+const ENTRY_SIZE: usize = 5;
+
+/// value of a 3x3 position for the side to move
+#[derive(Debug)]
+pub struct SolvedPosition {
+    /// score of the best move, as in `TicTacToe::get_move_scores`
+    pub score: i32,
+    pub best_moves: Vec<usize>,
+}
+
+/// looks up a position that is still being played, `None` when the table doesn't have it
+pub fn lookup(
+    symmetries: &Symmetries,
+    board: &Board,
+    player: &Player,
+    variant: Variant,
+) -> Option<SolvedPosition> {
+    if board.len() != 9 {
+        return None;
+    }
+
+    let (board, symmetry) = symmetries.get_canonical_for(board, player);
+    let code = get_code(&board);
+
+    let mut section = TABLE;
+    let sections_before = match variant {
+        Variant::Standard => 0,
+        Variant::Misere => 1,
+    };
+    for _ in 0..sections_before {
+        let count = u16::from_le_bytes([*section.first()?, *section.get(1)?]) as usize;
+        section = section.get(2 + count * ENTRY_SIZE..)?;
+    }
+    let count = u16::from_le_bytes([*section.first()?, *section.get(1)?]) as usize;
+    let entries: Vec<&[u8]> = section
+        .get(2..2 + count * ENTRY_SIZE)?
+        .chunks_exact(ENTRY_SIZE)
+        .collect();
+
+    let index = entries
+        .binary_search_by_key(&code, |entry| u16::from_le_bytes([entry[0], entry[1]]))
+        .ok()?;
+    let entry = entries[index];
+    let mask = u16::from_le_bytes([entry[3], entry[4]]);
+
+    // Moves are stored for the canonical board, map them back to this one
+    Some(SolvedPosition {
+        score: entry[2] as i8 as i32,
+        best_moves: (0..9)
+            .filter(|cell| mask & (1 << cell) != 0)
+            .map(|cell| Symmetries::get_original_cell(symmetry, cell))
+            .collect(),
+    })
+}
+
+/// solves every position of both variants with a full search and writes the table to `path`
+pub fn generate(path: &Path) -> io::Result<()> {
+    fs::write(path, get_table())
+}
+
+/// table of every position of both variants, solved with a full search
+fn get_table() -> Vec<u8> {
+    let mut data = vec![];
+    for variant in [Variant::Standard, Variant::Misere] {
+        let engine = TicTacToe::new(3, 3, variant);
+        let mut entries = BTreeMap::new();
+        add_positions(&engine, &vec![Cell::Empty; 9], &mut entries);
+
+        data.extend((entries.len() as u16).to_le_bytes());
+        for (code, (score, mask)) in entries {
+            data.extend(code.to_le_bytes());
+            data.push(score as u8);
+            data.extend(mask.to_le_bytes());
+        }
+    }
+    data
+}
+
+/// adds the canonical form of `board` (X to move) and every position reachable from it
+fn add_positions(engine: &TicTacToe, board: &Board, entries: &mut BTreeMap<u16, (i8, u16)>) {
+    if engine.get_game_result(board) != GameResult::Playing {
+        return;
+    }
+
+    let (board, _) = engine.symmetries.get_canonical(board);
+    let code = get_code(&board);
+    if entries.contains_key(&code) {
+        return;
+    }
+
+    let move_scores = engine.search_move_scores(&board, Player::X, -1);
+    let best = move_scores
+        .iter()
+        .map(|(_, score)| *score)
+        .max()
+        .unwrap_or(0);
+    let mask = move_scores
+        .iter()
+        .filter(|(_, score)| *score == best)
+        .fold(0u16, |mask, (index, _)| mask | (1 << index));
+    entries.insert(code, (best as i8, mask));
+
+    for index in TicTacToe::get_available_moves(&board) {
+        let mut child = board.clone();
+        child[index] = Cell::Mark(Player::X);
+        // O is to move, rotate the colours so the side to move is X again
+        let child = child
+            .iter()
+            .map(|cell| cell.get_rotated(Player::COUNT - 1))
+            .collect();
+        add_positions(engine, &child, entries);
+    }
+}
+
+/// base-3 number of the board, cell 0 being the lowest digit
+fn get_code(board: &Board) -> u16 {
+    board.iter().rev().fold(0, |code, cell| {
+        code * 3
+            + match cell {
+                Cell::Empty => 0,
+                Cell::Mark(Player::X) => 1,
+                Cell::Mark(Player::O) => 2,
+            }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Position codes follow the order of `Cell` and `Player`, a change there leaves the table
+    // missing every lookup until it's regenerated
+    #[test]
+    fn table_is_up_to_date() {
+        assert!(
+            get_table() == TABLE,
+            "regenerate with `tictactoe-tui --generate-solved-table data/solved_3x3.bin`"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::entities::{Board, Player};
+
+/// the 8 rotations & reflections of a square board, cell `i` of a transformed board is cell
+/// `symmetry[i]` of the original
+#[derive(Debug, Clone)]
+pub struct Symmetries {
+    symmetries: Vec<Vec<usize>>,
+    /// cell of the transformed board for each cell of the original, by symmetry
+    inverses: Vec<Vec<usize>>,
+}
+
+impl Symmetries {
+    pub fn new(size: u16) -> Self {
+        let size = size as usize;
+        let symmetries: Vec<Vec<usize>> = (0..8)
+            .map(|index| {
+                (0..size * size)
+                    .map(|cell| {
+                        let (mut row, mut col) = (cell / size, cell % size);
+                        if index >= 4 {
+                            col = size - 1 - col;
+                        }
+                        for _ in 0..index % 4 {
+                            (row, col) = (col, size - 1 - row);
+                        }
+                        row * size + col
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let inverses = symmetries
+            .iter()
+            .map(|symmetry| {
+                let mut inverse = vec![0; symmetry.len()];
+                for (cell, &source) in symmetry.iter().enumerate() {
+                    inverse[source] = cell;
+                }
+                inverse
+            })
+            .collect();
+
+        Symmetries {
+            symmetries,
+            inverses,
+        }
+    }
+
+    /// the smallest of the transformed boards, the same for every orientation of a position,
+    /// with the symmetry that gives it
+    pub fn get_canonical(&self, board: &Board) -> (Board, &[usize]) {
+        self.get_all()
+            .map(|symmetry| (Symmetries::apply(symmetry, board), symmetry))
+            .min_by(|(board, _), (other_board, _)| board.cmp(other_board))
+            .unwrap()
+    }
+
+    /// canonical board with the side to move as X, positions only differing by colours share
+    /// their value since the rules don't depend on them
+    pub fn get_canonical_for(&self, board: &Board, player: &Player) -> (Board, &[usize]) {
+        match (Player::COUNT - player.get_index()) % Player::COUNT {
+            0 => self.get_canonical(board),
+            steps => {
+                let board = board.iter().map(|cell| cell.get_rotated(steps)).collect();
+                self.get_canonical(&board)
+            }
+        }
+    }
+
+    /// every symmetry, the identity first
+    pub fn get_all(&self) -> impl Iterator<Item = &[usize]> {
+        self.symmetries.iter().map(Vec::as_slice)
+    }
+
+    /// cell of each transformed board for a cell of the original, by symmetry
+    pub fn get_transformed_cells(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        self.inverses.iter().map(move |inverse| inverse[cell])
+    }
+
+    pub fn apply(symmetry: &[usize], board: &Board) -> Board {
+        symmetry.iter().map(|&cell| board[cell].clone()).collect()
+    }
+
+    /// cell of the original board for a cell of the transformed one
+    pub fn get_original_cell(symmetry: &[usize], cell: usize) -> usize {
+        symmetry[cell]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tictactoe::TicTacToe;
+
+    #[test]
+    fn cells_map_back_to_the_original() {
+        for size in [3, 4, 5] {
+            let symmetries = Symmetries::new(size);
+            for cell in 0..(size * size) as usize {
+                for (symmetry, transformed) in symmetries
+                    .get_all()
+                    .zip(symmetries.get_transformed_cells(cell))
+                {
+                    assert_eq!(Symmetries::get_original_cell(symmetry, transformed), cell);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn orientations_share_the_canonical_board() {
+        for position in [
+            "x../.o./... x 3 3",
+            "xo./.x./..o o 3 3",
+            "x.o./.x../o..x/.... o 4 4",
+            "x..../.o.../..x../...o./..... x 5 4",
+        ] {
+            let parsed = TicTacToe::parse_position(position).unwrap();
+            let board = parsed.board;
+            let symmetries = Symmetries::new(parsed.size);
+            let (canonical, _) = symmetries.get_canonical(&board);
+            for symmetry in symmetries.get_all() {
+                let transformed = Symmetries::apply(symmetry, &board);
+                assert_eq!(symmetries.get_canonical(&transformed).0, canonical);
+            }
+        }
+    }
+}
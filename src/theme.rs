@@ -0,0 +1,292 @@
+use std::{fmt, str::FromStr};
+
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::entities::{MoveQuality, Outcome, Player, Threat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    Default,
+    HighContrast,
+    /// Okabe-Ito palette
+    ColourBlind,
+    /// No colours, also used when `NO_COLOR` is set
+    Monochrome,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub x: Color,
+    pub o: Color,
+    pub cell_border: Color,
+    pub selection: Color,
+    pub winner: Color,
+    pub border: Color,
+    pub title: Color,
+    pub muted: Color,
+    pub hint: Color,
+    pub win: Color,
+    pub draw: Color,
+    pub loss: Color,
+}
+
+/// `[theme]` table of the config file, every colour overrides the preset
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub preset: Option<ThemePreset>,
+    pub x: Option<String>,
+    pub o: Option<String>,
+    pub cell_border: Option<String>,
+    pub selection: Option<String>,
+    pub winner: Option<String>,
+    pub border: Option<String>,
+    pub title: Option<String>,
+    pub muted: Option<String>,
+    pub hint: Option<String>,
+    pub win: Option<String>,
+    pub draw: Option<String>,
+    pub loss: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct InvalidColor {
+    pub key: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for InvalidColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid colour `{}` for `theme.{}` (expected a name like `light-red`, an index like `208` or a hex value like `#ff8800`)",
+            self.value, self.key
+        )
+    }
+}
+
+impl ThemePreset {
+    pub fn get_theme(&self) -> Theme {
+        match self {
+            ThemePreset::Default => Theme {
+                x: Color::Red,
+                o: Color::Green,
+                cell_border: Color::DarkGray,
+                selection: Color::LightYellow,
+                winner: Color::Yellow,
+                border: Color::Reset,
+                title: Color::Yellow,
+                muted: Color::DarkGray,
+                hint: Color::LightCyan,
+                win: Color::Green,
+                draw: Color::Yellow,
+                loss: Color::Red,
+            },
+            ThemePreset::HighContrast => Theme {
+                x: Color::LightRed,
+                o: Color::LightCyan,
+                cell_border: Color::White,
+                selection: Color::LightYellow,
+                winner: Color::LightMagenta,
+                border: Color::White,
+                title: Color::LightYellow,
+                muted: Color::White,
+                hint: Color::LightCyan,
+                win: Color::LightGreen,
+                draw: Color::LightYellow,
+                loss: Color::LightRed,
+            },
+            // Okabe-Ito palette, distinguishable with every common colour vision deficiency
+            ThemePreset::ColourBlind => Theme {
+                x: Color::Rgb(213, 94, 0),
+                o: Color::Rgb(0, 114, 178),
+                cell_border: Color::DarkGray,
+                selection: Color::Rgb(240, 228, 66),
+                winner: Color::Rgb(204, 121, 167),
+                border: Color::Reset,
+                title: Color::Rgb(230, 159, 0),
+                muted: Color::DarkGray,
+                hint: Color::Rgb(86, 180, 233),
+                win: Color::Rgb(0, 158, 115),
+                draw: Color::Rgb(240, 228, 66),
+                loss: Color::Rgb(213, 94, 0),
+            },
+            ThemePreset::Monochrome => Theme {
+                x: Color::Reset,
+                o: Color::Reset,
+                cell_border: Color::Reset,
+                selection: Color::Reset,
+                winner: Color::Reset,
+                border: Color::Reset,
+                title: Color::Reset,
+                muted: Color::Reset,
+                hint: Color::Reset,
+                win: Color::Reset,
+                draw: Color::Reset,
+                loss: Color::Reset,
+            },
+        }
+    }
+}
+
+impl Theme {
+    /// resolves the theme from the config file, falling back to `NO_COLOR` and then the default preset
+    pub fn from_config(config: &ThemeConfig) -> Result<Theme, InvalidColor> {
+        let preset = config.preset.unwrap_or_else(|| {
+            let is_customized = config.x.is_some()
+                || config.o.is_some()
+                || config.cell_border.is_some()
+                || config.selection.is_some()
+                || config.winner.is_some()
+                || config.border.is_some()
+                || config.title.is_some()
+                || config.muted.is_some()
+                || config.hint.is_some()
+                || config.win.is_some()
+                || config.draw.is_some()
+                || config.loss.is_some();
+
+            if !is_customized && Theme::is_no_color() {
+                ThemePreset::Monochrome
+            } else {
+                ThemePreset::Default
+            }
+        });
+
+        let mut theme = preset.get_theme();
+        let overrides = [
+            ("x", &config.x, &mut theme.x),
+            ("o", &config.o, &mut theme.o),
+            ("cell-border", &config.cell_border, &mut theme.cell_border),
+            ("selection", &config.selection, &mut theme.selection),
+            ("winner", &config.winner, &mut theme.winner),
+            ("border", &config.border, &mut theme.border),
+            ("title", &config.title, &mut theme.title),
+            ("muted", &config.muted, &mut theme.muted),
+            ("hint", &config.hint, &mut theme.hint),
+            ("win", &config.win, &mut theme.win),
+            ("draw", &config.draw, &mut theme.draw),
+            ("loss", &config.loss, &mut theme.loss),
+        ];
+        for (key, value, color) in overrides {
+            if let Some(value) = value {
+                *color = Color::from_str(value).map_err(|_| InvalidColor {
+                    key,
+                    value: value.clone(),
+                })?;
+            }
+        }
+
+        Ok(theme)
+    }
+
+    pub fn get_outcome_color(&self, outcome: &Outcome) -> Color {
+        match outcome {
+            Outcome::Win(_) => self.win,
+            Outcome::Draw => self.draw,
+            Outcome::Loss(_) => self.loss,
+            Outcome::Unknown => self.muted,
+        }
+    }
+
+    pub fn get_quality_color(&self, quality: &MoveQuality) -> Color {
+        match quality {
+            MoveQuality::Best => self.win,
+            MoveQuality::Ok => self.muted,
+            MoveQuality::Mistake => self.draw,
+            MoveQuality::Blunder => self.loss,
+        }
+    }
+
+    pub fn get_threat_color(&self, threat: &Threat) -> Color {
+        match threat {
+            Threat::Win => self.win,
+            Threat::Block => self.loss,
+            Threat::Fork => self.draw,
+            Threat::Lose => self.loss,
+            Threat::Avoid => self.draw,
+        }
+    }
+
+    pub fn get_player_color(&self, player: &Player) -> Color {
+        match player {
+            Player::X => self.x,
+            Player::O => self.o,
+        }
+    }
+
+    // https://no-color.org
+    fn is_no_color() -> bool {
+        std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemePreset::Default.get_theme()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbols {
+    pub x: String,
+    pub o: String,
+}
+
+/// `[symbols]` table of the config file
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SymbolsConfig {
+    pub x: Option<String>,
+    pub o: Option<String>,
+}
+
+impl Symbols {
+    pub fn from_config(config: &SymbolsConfig) -> Result<Symbols, String> {
+        let default = Symbols::default();
+        let symbols = Symbols {
+            x: config.x.clone().unwrap_or(default.x),
+            o: config.o.clone().unwrap_or(default.o),
+        };
+
+        for (key, symbol) in [("x", &symbols.x), ("o", &symbols.o)] {
+            let mut chars = symbol.chars();
+            match (chars.next(), chars.next()) {
+                (Some(char), None) if !char.is_whitespace() => {}
+                _ => {
+                    return Err(format!(
+                    "invalid symbol `{}` for `symbols.{}` (expected a single visible character)",
+                    symbol, key
+                ))
+                }
+            }
+        }
+        if symbols.x == symbols.o {
+            return Err(format!(
+                "`symbols.x` and `symbols.o` are both `{}`",
+                symbols.x
+            ));
+        }
+
+        Ok(symbols)
+    }
+
+    pub fn get_player_symbol(&self, player: &Player) -> &str {
+        match player {
+            Player::X => &self.x,
+            Player::O => &self.o,
+        }
+    }
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        Symbols {
+            x: "X".to_string(),
+            o: "O".to_string(),
+        }
+    }
+}
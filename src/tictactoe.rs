@@ -1,225 +1,1240 @@
-use std::{cmp::max, cmp::min, collections::HashMap};
+use std::{
+    cmp::{max, min},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng};
 
 use crate::{
-    constants::BOARD_SIZE,
-    entities::{Board, GameResult, Player},
+    bitboard::{Bitboard, Mask},
+    constants::{
+        EVALUATION_SCALE, MAX_BOARD_SIZE, MAX_EVALUATION, MAX_SEARCH_NODES, MIN_BOARD_SIZE,
+    },
+    entities::{
+        Board, Cell, GameResult, LineKind, Motif, Outcome, Player, Position, SearchReport, Threat,
+        Variant,
+    },
+    solved::{self, SolvedPosition},
+    symmetry::Symmetries,
+    zobrist::{HashedBoard, KeyTable, ZobristKeys},
 };
 
-#[derive(Debug)]
-pub struct TicTacToe {}
+#[derive(Debug, Clone)]
+pub struct TicTacToe {
+    pub size: u16,
+    pub win_length: u16,
+    pub variant: Variant,
+    pub symmetries: Symmetries,
+    /// threads sharing out the moves of a search
+    pub threads: usize,
+    zobrist_keys: ZobristKeys,
+    lines: Vec<Vec<usize>>,
+    /// lines through each cell, by cell
+    cell_lines: Vec<Vec<usize>>,
+    /// bitboard mask of every line
+    line_masks: Vec<Mask>,
+    /// bitboard mask of every cell
+    cells_mask: Mask,
+}
+
+/// board of a search, with the marks of every side in every line kept up to date move by move
+#[derive(Debug, Clone)]
+struct SearchBoard {
+    hashed: HashedBoard,
+    /// marks of each player in the order of `Player::ALL`, by line
+    line_counts: Vec<[u32; Player::COUNT]>,
+    /// lines completed by each player
+    completed_lines: [u32; Player::COUNT],
+    /// weight of the lines each player holds alone, see `add_line_value`
+    line_totals: [i64; Player::COUNT],
+}
+
+/// state shared by the searches of a move
+#[derive(Debug, Default)]
+struct Search<'a> {
+    table: KeyTable<TableEntry>,
+    nodes: u64,
+    table_hits: u64,
+    /// the search gives up once it's past
+    deadline: Option<Instant>,
+    /// the search gives up once it's set
+    cancel: Option<&'a AtomicBool>,
+    is_aborted: bool,
+    /// searches of the other threads, kept with their tables from one depth to the next
+    workers: Vec<Search<'a>>,
+}
+
+impl Search<'_> {
+    /// nodes searched by every thread
+    fn get_nodes(&self) -> u64 {
+        self.nodes + self.workers.iter().map(Search::get_nodes).sum::<u64>()
+    }
+
+    fn get_table_hits(&self) -> u64 {
+        self.table_hits + self.workers.iter().map(Search::get_table_hits).sum::<u64>()
+    }
+
+    /// entry of a position in the table of any thread
+    fn get_entry(&self, key: u64) -> Option<&TableEntry> {
+        self.table
+            .get(&key)
+            .or_else(|| self.workers.iter().find_map(|worker| worker.get_entry(key)))
+    }
+}
+
+/// searched value of a position, by Zobrist key
+#[derive(Debug, Clone, Copy)]
+struct TableEntry {
+    /// moves searched below the position, `i32::MAX` for every end of the game
+    remaining_depth: i32,
+    /// score for the side to move, with wins & losses counted from the position
+    score: i32,
+}
 
 impl TicTacToe {
+    pub fn new(size: u16, win_length: u16, variant: Variant) -> Self {
+        let size_i = size as i32;
+        let mut lines = vec![];
+
+        // Every horizontal, vertical & diagonal run of `win_length` cells
+        for row in 0..size_i {
+            for col in 0..size_i {
+                for (d_row, d_col) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                    let end_row = row + d_row * (win_length as i32 - 1);
+                    let end_col = col + d_col * (win_length as i32 - 1);
+                    if !(0..size_i).contains(&end_row) || !(0..size_i).contains(&end_col) {
+                        continue;
+                    }
+
+                    let line: Vec<usize> = (0..win_length as i32)
+                        .map(|step| ((row + d_row * step) * size_i + col + d_col * step) as usize)
+                        .collect();
+                    lines.push(line);
+                }
+            }
+        }
+
+        let cell_lines = (0..(size * size) as usize)
+            .map(|index| {
+                (0..lines.len())
+                    .filter(|&line| lines[line].contains(&index))
+                    .collect()
+            })
+            .collect();
+
+        let symmetries = Symmetries::new(size);
+        TicTacToe {
+            size,
+            win_length,
+            variant,
+            threads: 1,
+            zobrist_keys: ZobristKeys::new(&symmetries, size),
+            symmetries,
+            line_masks: lines
+                .iter()
+                .map(|line| Mask::from_cells(line.iter().copied()))
+                .collect(),
+            lines,
+            cell_lines,
+            cells_mask: Mask::get_first((size * size) as usize),
+        }
+    }
+
+    pub fn get_default_win_length(size: u16) -> u16 {
+        min(size, 4)
+    }
+
+    /// index of the best move for `player`, or a random one if multiple moves have the same heuristic value,
+    /// with what the search did. `None` once `cancel` is set
     pub fn get_best_move(
+        &self,
         board: &Board,
         player: Player,
-        is_maximizing: bool,
+        max_depth: i32,
+        cancel: &AtomicBool,
+        rng: &mut impl Rng,
+    ) -> Option<(usize, SearchReport)> {
+        let started = Instant::now();
+        if max_depth == -1 && self.size == 3 {
+            if let Some(solved) = solved::lookup(&self.symmetries, board, &player, self.variant) {
+                return self.get_solved_move(board, player, solved, started, rng);
+            }
+        }
+        if self.get_game_result(board) != GameResult::Playing {
+            return None;
+        }
+
+        let mut search = Search {
+            cancel: Some(cancel),
+            ..Search::default()
+        };
+        let mut search_board = self.get_search_board(board);
+        let move_scores = self.search_root(&mut search_board, &player, max_depth, &mut search);
+        if search.is_aborted {
+            return None;
+        }
+
+        self.get_searched_move(
+            board,
+            &player,
+            move_scores,
+            max_depth,
+            &search,
+            started,
+            rng,
+        )
+    }
+
+    /// best move for `player` from searching one move deeper at a time until `think_time` is up,
+    /// as found by the last search that finished, with how far it got. `None` once `cancel` is set
+    pub fn get_timed_best_move(
+        &self,
+        board: &Board,
+        player: Player,
+        think_time: Duration,
+        cancel: &AtomicBool,
+        rng: &mut impl Rng,
+    ) -> Option<(usize, SearchReport)> {
+        let started = Instant::now();
+        if self.size == 3 {
+            if let Some(solved) = solved::lookup(&self.symmetries, board, &player, self.variant) {
+                return self.get_solved_move(board, player, solved, started, rng);
+            }
+        }
+        if self.get_game_result(board) != GameResult::Playing {
+            return None;
+        }
+
+        let deadline = started + think_time;
+        let available_moves = TicTacToe::get_available_moves(board).len() as i32;
+        let mut search = Search {
+            cancel: Some(cancel),
+            ..Search::default()
+        };
+        let mut search_board = self.get_search_board(board);
+        let mut move_scores = vec![];
+        let mut search_depth = 0;
+
+        for depth in 1.. {
+            let max_depth = if depth >= available_moves { -1 } else { depth };
+            let scores = self.search_root(&mut search_board, &player, max_depth, &mut search);
+            if search.is_aborted {
+                break;
+            }
+
+            // The first search always finishes unless cancelled, so there is a move to play
+            search.deadline = Some(deadline);
+            move_scores = scores;
+            search_depth = max_depth;
+            let best = move_scores.iter().map(|(_, score)| *score).max()?;
+            if max_depth == -1 || best > MAX_EVALUATION || Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        self.get_searched_move(
+            board,
+            &player,
+            move_scores,
+            search_depth,
+            &search,
+            started,
+            rng,
+        )
+    }
+
+    /// random move among the best of `move_scores`, found by `search` as deep as `depth`, with
+    /// what the search did
+    #[allow(clippy::too_many_arguments)]
+    fn get_searched_move(
+        &self,
+        board: &Board,
+        player: &Player,
+        move_scores: Vec<(usize, i32)>,
         depth: i32,
-        nodes_map: &mut HashMap<i32, Vec<i32>>,
-    ) -> i32 {
-        let max_depth = -1;
+        search: &Search,
+        started: Instant,
+        rng: &mut impl Rng,
+    ) -> Option<(usize, SearchReport)> {
+        let best = move_scores.iter().map(|(_, score)| *score).max()?;
+        let index = move_scores
+            .into_iter()
+            .filter(|(_, score)| *score == best)
+            .map(|(index, _)| index)
+            .choose(rng)?;
+        let report = SearchReport {
+            depth,
+            nodes: search.get_nodes(),
+            table_hits: search.get_table_hits(),
+            elapsed: started.elapsed(),
+            principal_variation: self.get_principal_variation(board, player, index, depth, search),
+        };
+        Some((index, report))
+    }
 
-        // If the board state is a terminal one, return the heuristic value
-        let result = TicTacToe::get_game_result(&board);
-        if result != GameResult::Playing || depth == max_depth {
-            if result == GameResult::Win(player.clone()) {
-                return 100 - depth;
-            } else if result == GameResult::Win(player.get_opponent()) {
-                return -100 + depth;
+    /// random best move of a position of the solved table, the variation following the first
+    /// best move of every position after it
+    fn get_solved_move(
+        &self,
+        board: &Board,
+        player: Player,
+        solved: SolvedPosition,
+        started: Instant,
+        rng: &mut impl Rng,
+    ) -> Option<(usize, SearchReport)> {
+        let index = solved.best_moves.into_iter().choose(rng)?;
+
+        let mut board = board.clone();
+        let mut player = player;
+        let mut principal_variation = vec![];
+        let mut next = Some(index);
+        while let Some(index) = next {
+            principal_variation.push(index);
+            board[index] = Cell::Mark(player.clone());
+            player = player.get_next();
+            next = solved::lookup(&self.symmetries, &board, &player, self.variant)
+                .and_then(|solved| solved.best_moves.first().copied());
+        }
+
+        let report = SearchReport {
+            depth: -1,
+            elapsed: started.elapsed(),
+            principal_variation,
+            ..SearchReport::default()
+        };
+        Some((index, report))
+    }
+
+    /// moves expected after `player` plays `index`, each side replying with its best move the
+    /// table knows the score of
+    fn get_principal_variation(
+        &self,
+        board: &Board,
+        player: &Player,
+        index: usize,
+        max_depth: i32,
+        search: &Search,
+    ) -> Vec<usize> {
+        let mut board = self.get_search_board(board);
+        let mut variation = vec![index];
+        let mut side_to_move = player.clone();
+        self.make_move(&mut board, index, &side_to_move);
+
+        for depth in 1.. {
+            side_to_move = side_to_move.get_next();
+            if depth == max_depth || self.get_search_result(&board) != GameResult::Playing {
+                break;
+            }
+
+            let moves = !board.hashed.bitboard.get_occupied() & self.cells_mask;
+            let scores = moves.get_cells().filter_map(|next| {
+                self.make_move(&mut board, next, &side_to_move);
+                let score = self.get_known_score(&board, player, depth + 1, max_depth, search);
+                self.unmake_move(&mut board, next, &side_to_move);
+                score.map(|score| (next, score))
+            });
+            let next = if &side_to_move == player {
+                scores.max_by_key(|&(_, score)| score)
             } else {
-                return 0;
+                scores.min_by_key(|&(_, score)| score)
+            };
+            let Some((next, _)) = next else {
+                break;
+            };
+            self.make_move(&mut board, next, &side_to_move);
+            variation.push(next);
+        }
+
+        variation
+    }
+
+    /// score for `player` of a position `depth` moves below the root as `minimax` found it,
+    /// `None` when it isn't in the table
+    fn get_known_score(
+        &self,
+        board: &SearchBoard,
+        player: &Player,
+        depth: i32,
+        max_depth: i32,
+        search: &Search,
+    ) -> Option<i32> {
+        match self.get_search_result(board) {
+            GameResult::Win(winner) if &winner == player => return Some(100 - depth),
+            GameResult::Win(_) => return Some(-100 + depth),
+            GameResult::Draw => return Some(0),
+            GameResult::Playing if depth == max_depth => return Some(self.evaluate(board, player)),
+            GameResult::Playing => {}
+        }
+
+        // The sides move in turn from the root player
+        let side_to_move = player.get_rotated(depth as usize);
+        let entry = search.get_entry(board.hashed.get_key(&side_to_move))?;
+        let score = TicTacToe::get_absolute_score(entry.score, depth);
+        Some(if &side_to_move == player {
+            score
+        } else {
+            -score
+        })
+    }
+
+    /// heuristic value of every available move for `player`: `100 - depth` for a win,
+    /// `-100 + depth` for a loss and 0 for a draw or when `max_depth` is reached
+    pub fn get_move_scores(
+        &self,
+        board: &Board,
+        player: Player,
+        max_depth: i32,
+    ) -> Vec<(usize, i32)> {
+        if max_depth == -1 && self.size == 3 {
+            if let Some(move_scores) = self.get_solved_move_scores(board, &player) {
+                return move_scores;
             }
         }
 
-        if is_maximizing {
-            // Initialize best to the lowest possible value
-            let mut best = -100;
+        self.search_move_scores(board, player, max_depth)
+    }
+
+    /// `get_move_scores` as deep as `max_depth` and `MAX_SEARCH_NODES` allow with the outcome of
+    /// every move, best first, and the depth searched
+    pub fn get_move_outcomes(
+        &self,
+        board: &Board,
+        player: &Player,
+        max_depth: i32,
+    ) -> (Vec<(usize, i32, Outcome)>, i32) {
+        let max_depth = self.get_search_depth(board, max_depth);
+        let mut move_scores = self.get_move_scores(board, player.clone(), max_depth);
+        move_scores.sort_by_key(|&(index, score)| (-score, index));
+
+        let move_outcomes = move_scores
+            .into_iter()
+            .map(|(index, score)| (index, score, Outcome::from_score(score, max_depth == -1)))
+            .collect();
+        (move_outcomes, max_depth)
+    }
+
+    /// `get_move_scores` from the solved 3x3 table, every move leads to a finished game or a
+    /// position of the table
+    fn get_solved_move_scores(&self, board: &Board, player: &Player) -> Option<Vec<(usize, i32)>> {
+        if self.get_game_result(board) != GameResult::Playing {
+            return Some(vec![]);
+        }
 
-            // Loop through all empty cells
-            let available_moves = TicTacToe::get_available_moves(&board);
-            for index in available_moves {
+        TicTacToe::get_available_moves(board)
+            .into_iter()
+            .map(|index| {
                 let mut board_2 = board.clone();
-                board_2[index] = player.clone();
-
-                let node_value =
-                    TicTacToe::get_best_move(&board_2, player.clone(), false, depth + 1, nodes_map);
-
-                best = max(best, node_value);
-
-                // If it's the main function call, not a recursive one, map each heuristic value with it's moves indices
-                if depth == 0 {
-                    //Comma separated indices if multiple moves have the same heuristic value
-                    let moves: Vec<i32> = if let Some(moves) = nodes_map.get(&node_value).clone() {
-                        let mut moves = moves.clone();
-                        moves.push(index as i32);
-                        moves
-                    } else {
-                        vec![index as i32]
-                    };
-                    nodes_map.insert(node_value, moves);
-                }
+                board_2[index] = Cell::Mark(player.clone());
+
+                // One move deeper than the opponent's best reply, from the other side
+                let score = match self.get_game_result(&board_2) {
+                    GameResult::Playing => {
+                        let opponent = player.get_next();
+                        match solved::lookup(&self.symmetries, &board_2, &opponent, self.variant)?
+                            .score
+                        {
+                            score if score > 0 => -score + 1,
+                            score if score < 0 => -score - 1,
+                            _ => 0,
+                        }
+                    }
+                    GameResult::Win(winner) if &winner == player => 99,
+                    GameResult::Win(_) => -99,
+                    GameResult::Draw => 0,
+                };
+                Some((index, score))
+            })
+            .collect()
+    }
+
+    /// `get_move_scores` with a minimax search only
+    pub fn search_move_scores(
+        &self,
+        board: &Board,
+        player: Player,
+        max_depth: i32,
+    ) -> Vec<(usize, i32)> {
+        if self.get_game_result(board) != GameResult::Playing {
+            return vec![];
+        }
+
+        let mut board = self.get_search_board(board);
+        self.search_root(&mut board, &player, max_depth, &mut Search::default())
+    }
+
+    /// scores of every available move, the searches share their table since they reach the same
+    /// positions
+    fn search_root(
+        &self,
+        board: &mut SearchBoard,
+        player: &Player,
+        max_depth: i32,
+        search: &mut Search,
+    ) -> Vec<(usize, i32)> {
+        let moves: Vec<usize> = (!board.hashed.bitboard.get_occupied() & self.cells_mask)
+            .get_cells()
+            .collect();
+        if self.threads > 1 && moves.len() > 1 {
+            return self.search_root_in_parallel(board, player, max_depth, search, &moves);
+        }
+
+        moves
+            .into_iter()
+            .map(|index| {
+                self.make_move(board, index, player);
+                let score = self.minimax(board, player, 1, max_depth, search);
+                self.unmake_move(board, index, player);
+                (index, score)
+            })
+            .collect()
+    }
+
+    /// `search_root` with the moves handed out to the threads one at a time, every move gets the
+    /// same score as in a single thread since a position is always searched to the same depth
+    fn search_root_in_parallel(
+        &self,
+        board: &SearchBoard,
+        player: &Player,
+        max_depth: i32,
+        search: &mut Search,
+        moves: &[usize],
+    ) -> Vec<(usize, i32)> {
+        let threads = self.threads.min(moves.len());
+        while search.workers.len() < threads {
+            search.workers.push(Search::default());
+        }
+
+        let next_move = AtomicUsize::new(0);
+        let move_scores = Mutex::new(Vec::with_capacity(moves.len()));
+        let (deadline, cancel) = (search.deadline, search.cancel);
+        thread::scope(|scope| {
+            for worker in &mut search.workers[..threads] {
+                let mut board = board.clone();
+                let (next_move, move_scores) = (&next_move, &move_scores);
+                worker.deadline = deadline;
+                worker.cancel = cancel;
+                scope.spawn(move || {
+                    while let Some(&index) = moves.get(next_move.fetch_add(1, Ordering::Relaxed)) {
+                        self.make_move(&mut board, index, player);
+                        let score = self.minimax(&mut board, player, 1, max_depth, worker);
+                        self.unmake_move(&mut board, index, player);
+                        move_scores.lock().unwrap().push((index, score));
+                    }
+                });
             }
+        });
+
+        search.is_aborted |= search.workers.iter().any(|worker| worker.is_aborted);
+        let mut move_scores = move_scores.into_inner().unwrap();
+        move_scores.sort();
+        move_scores
+    }
 
-            // If it's the main call, return the index of the best move or a random index if multiple indices have the same value
-            if depth == 0 {
-                let moves = nodes_map.get(&best).unwrap().clone();
-                let return_value: i32;
+    fn get_search_board(&self, board: &Board) -> SearchBoard {
+        let mut search_board = SearchBoard {
+            hashed: HashedBoard::new(&self.zobrist_keys, Bitboard::default()),
+            line_counts: vec![[0; Player::COUNT]; self.lines.len()],
+            completed_lines: [0; Player::COUNT],
+            line_totals: [0; Player::COUNT],
+        };
+        for (index, cell) in board.iter().enumerate() {
+            if let Cell::Mark(player) = cell {
+                self.make_move(&mut search_board, index, player);
+            }
+        }
+        search_board
+    }
 
-                if moves.len() > 1 {
-                    return_value = moves
-                        .iter()
-                        .choose(&mut rand::thread_rng())
-                        .unwrap_or(&0i32)
-                        .clone();
-                } else {
-                    return_value = moves[0] as i32;
-                }
+    fn make_move(&self, board: &mut SearchBoard, index: usize, player: &Player) {
+        board.hashed.make_move(&self.zobrist_keys, index, player);
 
-                return return_value;
+        let side = player.get_index();
+        for &line in &self.cell_lines[index] {
+            let counts = &mut board.line_counts[line];
+            TicTacToe::add_line_value(&mut board.line_totals, counts, -1);
+            counts[side] += 1;
+            TicTacToe::add_line_value(&mut board.line_totals, counts, 1);
+            if counts[side] == self.win_length as u32 {
+                board.completed_lines[side] += 1;
             }
+        }
+    }
+
+    /// takes back a move made with `make_move`
+    fn unmake_move(&self, board: &mut SearchBoard, index: usize, player: &Player) {
+        board.hashed.unmake_move(&self.zobrist_keys, index, player);
 
-            // If not main call (recursive) return the heuristic value for next calculation
-            return best;
+        let side = player.get_index();
+        for &line in &self.cell_lines[index] {
+            let counts = &mut board.line_counts[line];
+            if counts[side] == self.win_length as u32 {
+                board.completed_lines[side] -= 1;
+            }
+            TicTacToe::add_line_value(&mut board.line_totals, counts, -1);
+            counts[side] -= 1;
+            TicTacToe::add_line_value(&mut board.line_totals, counts, 1);
         }
+    }
 
-        if !is_maximizing {
-            // Initialize best to the lowest possible value
-            let mut best = 100;
+    /// adds `sign` times the weight of a line to the player holding it alone, four times more
+    /// with each mark it holds and nothing once two sides have a mark in it
+    fn add_line_value(
+        line_totals: &mut [i64; Player::COUNT],
+        counts: &[u32; Player::COUNT],
+        sign: i64,
+    ) {
+        let mut sides = counts.iter().enumerate().filter(|(_, &count)| count > 0);
+        if let (Some((side, &count)), None) = (sides.next(), sides.next()) {
+            line_totals[side] += sign * ((1 << (2 * count)) - 1);
+        }
+    }
 
-            // Loop through all empty cells
-            let available_moves = TicTacToe::get_available_moves(&board);
-            for index in available_moves {
-                let mut board_2 = board.clone();
-                board_2[index] = player.get_opponent().clone();
-
-                let node_value =
-                    TicTacToe::get_best_move(&board_2, player.clone(), true, depth + 1, nodes_map);
-
-                best = min(best, node_value);
-
-                // If it's the main function call, not a recursive one, map each heuristic value with it's moves indices
-                if depth == 0 {
-                    //Comma separated indices if multiple moves have the same heuristic value
-                    let moves: Vec<i32> = if let Some(moves) = nodes_map.get(&node_value).clone() {
-                        let mut moves = moves.clone();
-                        moves.push(index as i32);
-                        moves
-                    } else {
-                        vec![index as i32]
-                    };
-                    nodes_map.insert(node_value, moves);
-                }
+    fn get_search_result(&self, board: &SearchBoard) -> GameResult {
+        for player in Player::ALL {
+            if board.completed_lines[player.get_index()] > 0 {
+                return self.get_line_result(player);
             }
+        }
 
-            // If it's the main call, return the index of the best move or a random index if multiple indices have the same value
-            if depth == 0 {
-                let moves = nodes_map.get(&best).unwrap().clone();
-                let return_value: i32;
+        if board.hashed.bitboard.get_occupied() == self.cells_mask {
+            return GameResult::Draw;
+        }
 
-                if moves.len() > 1 {
-                    return_value = moves
-                        .iter()
-                        .choose(&mut rand::thread_rng())
-                        .unwrap_or(&0i32)
-                        .clone();
-                } else {
-                    return_value = moves[0] as i32;
-                }
+        GameResult::Playing
+    }
 
-                return return_value;
+    fn minimax(
+        &self,
+        board: &mut SearchBoard,
+        player: &Player,
+        depth: i32,
+        max_depth: i32,
+        search: &mut Search,
+    ) -> i32 {
+        // Checking the clock at every node would slow the search down
+        search.nodes += 1;
+        if search.nodes.is_multiple_of(1024)
+            && (search
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+                || search
+                    .cancel
+                    .is_some_and(|cancel| cancel.load(Ordering::Relaxed)))
+        {
+            search.is_aborted = true;
+        }
+        if search.is_aborted {
+            return 0;
+        }
+
+        // If the board state is a terminal one, return the heuristic value
+        match self.get_search_result(board) {
+            GameResult::Win(winner) if &winner == player => return 100 - depth,
+            GameResult::Win(_) => return -100 + depth,
+            GameResult::Draw => return 0,
+            GameResult::Playing if depth == max_depth => return self.evaluate(board, player),
+            GameResult::Playing => {}
+        }
+
+        // Every other side plays against `player`
+        let side_to_move = player.get_rotated(depth as usize);
+        let is_maximizing = &side_to_move == player;
+
+        // Rotations, reflections & colour swaps of a searched position have the same value
+        let key = board.hashed.get_key(&side_to_move);
+        let remaining_depth = if max_depth == -1 {
+            i32::MAX
+        } else {
+            max_depth - depth
+        };
+        if let Some(entry) = search.table.get(&key) {
+            if entry.remaining_depth >= remaining_depth {
+                search.table_hits += 1;
+                let score = TicTacToe::get_absolute_score(entry.score, depth);
+                return if is_maximizing { score } else { -score };
             }
+        }
+
+        // Initialize best to the lowest (or highest) possible value
+        let mut best = if is_maximizing { -100 } else { 100 };
+
+        // Loop through all empty cells
+        for index in (!board.hashed.bitboard.get_occupied() & self.cells_mask).get_cells() {
+            self.make_move(board, index, &side_to_move);
+            let node_value = self.minimax(board, player, depth + 1, max_depth, search);
+            self.unmake_move(board, index, &side_to_move);
+
+            best = if is_maximizing {
+                max(best, node_value)
+            } else {
+                min(best, node_value)
+            };
+        }
 
-            // If not main call (recursive) return the heuristic value for next calculation
-            return best;
+        // Positions left halfway have no value
+        if search.is_aborted {
+            return 0;
         }
 
-        return 0;
+        let score = if is_maximizing { best } else { -best };
+        search.table.insert(
+            key,
+            TableEntry {
+                remaining_depth,
+                score: TicTacToe::get_relative_score(score, depth),
+            },
+        );
+        best
+    }
+
+    /// static value of an unfinished position for `player` where the search stops, from the lines
+    /// still open to a single side
+    fn evaluate(&self, board: &SearchBoard, player: &Player) -> i32 {
+        // The player's lines against those of every other side
+        let own = board.line_totals[player.get_index()];
+        let total = 2 * own - board.line_totals.iter().sum::<i64>();
+        // Lines are to be avoided when completing one loses
+        let total = match self.variant {
+            Variant::Standard => total,
+            Variant::Misere => -total,
+        };
+
+        // Squeezed so it never reaches the score of a win or a loss
+        (MAX_EVALUATION as i64 * total / (total.abs() + EVALUATION_SCALE)) as i32
+    }
+
+    /// moves a win or loss found `depth` moves below the root to count from the position,
+    /// evaluations don't depend on the depth
+    fn get_relative_score(score: i32, depth: i32) -> i32 {
+        match score {
+            score if score > MAX_EVALUATION => score + depth,
+            score if score < -MAX_EVALUATION => score - depth,
+            _ => score,
+        }
+    }
+
+    fn get_absolute_score(score: i32, depth: i32) -> i32 {
+        match score {
+            score if score > MAX_EVALUATION => score - depth,
+            score if score < -MAX_EVALUATION => score + depth,
+            _ => score,
+        }
     }
 
     pub fn get_available_moves(board: &Board) -> Vec<usize> {
         board
             .iter()
             .enumerate()
-            .filter(|x| x.1 == &Player::None)
+            .filter(|x| x.1.is_empty())
             .map(|x| x.0)
             .collect::<Vec<usize>>()
     }
 
-    pub fn get_game_result(board: &Board) -> GameResult {
-        if TicTacToe::is_empty(&board) {
-            return GameResult::Playing;
+    /// caps `max_depth` so a full-width search stays within `MAX_SEARCH_NODES`
+    pub fn get_search_depth(&self, board: &Board, max_depth: i32) -> i32 {
+        let available_moves = TicTacToe::get_available_moves(board).len() as u64;
+
+        let mut depth = 0;
+        let mut nodes = 1u64;
+        while (depth as u64) < available_moves && (max_depth == -1 || depth < max_depth) {
+            nodes = nodes.saturating_mul(available_moves - depth as u64);
+            if nodes > MAX_SEARCH_NODES {
+                break;
+            }
+            depth += 1;
+        }
+
+        if depth as u64 == available_moves && max_depth == -1 {
+            -1
+        } else {
+            max(depth, 1)
         }
+    }
 
-        // 0 1 2
-        // 3 4 5
-        // 6 7 8
+    pub fn get_game_result(&self, board: &Board) -> GameResult {
+        self.get_bitboard_result(&Bitboard::from_board(board))
+    }
 
-        // Check Horizontal Wins
-        if &board[0] != &Player::None && &board[0] == &board[1] && &board[0] == &board[2] {
-            return GameResult::Win(board[0].clone());
+    fn get_bitboard_result(&self, bitboard: &Bitboard) -> GameResult {
+        // Horizontal, Vertical & Diagonal Wins
+        for player in Player::ALL {
+            let marks = bitboard.get_marks(&player);
+            if self
+                .line_masks
+                .iter()
+                .any(|&mask| (mask & !marks).is_empty())
+            {
+                return self.get_line_result(player);
+            }
         }
 
-        if &board[3] != &Player::None && &board[3] == &board[4] && &board[3] == &board[5] {
-            return GameResult::Win(board[3].clone());
+        // Draw
+        if bitboard.get_occupied() == self.cells_mask {
+            return GameResult::Draw;
         }
 
-        if &board[6] != &Player::None && &board[6] == &board[7] && &board[6] == &board[8] {
-            return GameResult::Win(board[6].clone());
+        GameResult::Playing
+    }
+
+    /// result of the game right after a move at `index`, only checking the lines through it
+    pub fn get_move_result(&self, board: &Board, index: usize) -> GameResult {
+        if let Cell::Mark(player) = &board[index] {
+            let is_line = self.cell_lines[index].iter().any(|&line| {
+                self.lines[line]
+                    .iter()
+                    .all(|&cell| board[cell].is_marked_by(player))
+            });
+            if is_line {
+                return self.get_line_result(player.clone());
+            }
         }
 
-        // Check Vertical Wins
-        if &board[0] != &Player::None && &board[0] == &board[3] && &board[0] == &board[6] {
-            return GameResult::Win(board[0].clone());
+        if board.iter().any(Cell::is_empty) {
+            GameResult::Playing
+        } else {
+            GameResult::Draw
         }
+    }
 
-        if &board[1] != &Player::None && &board[1] == &board[4] && &board[1] == &board[7] {
-            return GameResult::Win(board[1].clone());
+    /// result of the game once `player` completes a line
+    fn get_line_result(&self, player: Player) -> GameResult {
+        match self.variant {
+            Variant::Standard => GameResult::Win(player),
+            Variant::Misere => GameResult::Win(player.get_next()),
         }
+    }
+
+    pub fn get_winning_line(&self, board: &Board) -> Option<&[usize]> {
+        self.lines
+            .iter()
+            .find(|line| {
+                !board[line[0]].is_empty() && line.iter().all(|&i| board[i] == board[line[0]])
+            })
+            .map(|line| line.as_slice())
+    }
 
-        if &board[2] != &Player::None && &board[2] == &board[5] && &board[2] == &board[8] {
-            return GameResult::Win(board[2].clone());
+    /// rows top to bottom separated by `/`, then the side to move, the size and the win length,
+    /// e.g. `x../.o./... x 3 3`
+    pub fn format_position(&self, board: &Board, player: &Player) -> String {
+        let rows: Vec<String> = board
+            .chunks(self.size as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell::Mark(Player::X) => 'x',
+                        Cell::Mark(Player::O) => 'o',
+                        Cell::Empty => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        let player = if player == &Player::O { 'o' } else { 'x' };
+
+        format!(
+            "{} {} {} {}",
+            rows.join("/"),
+            player,
+            self.size,
+            self.win_length
+        )
+    }
+
+    /// reads a string written by `format_position`, the size and win length may be left out and
+    /// default to the number of rows and `get_default_win_length`
+    pub fn parse_position(position: &str) -> Result<Position, String> {
+        let fields: Vec<&str> = position.split_whitespace().collect();
+        let (rows, player) = match fields[..] {
+            [rows, player, ..] if fields.len() <= 4 => (rows, player),
+            _ => {
+                return Err(
+                    "expected the rows, the side to move, the size and the win length separated by spaces"
+                        .to_string(),
+                )
+            }
+        };
+
+        let rows: Vec<&str> = rows.split('/').collect();
+        let size = match fields.get(2) {
+            Some(size) => size
+                .parse::<u16>()
+                .map_err(|_| format!("invalid size `{}`", size))?,
+            None => rows.len() as u16,
+        };
+        if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+            return Err(format!(
+                "invalid size {} (expected {} to {})",
+                size, MIN_BOARD_SIZE, MAX_BOARD_SIZE
+            ));
+        }
+        let win_length = match fields.get(3) {
+            Some(win_length) => win_length
+                .parse::<u16>()
+                .map_err(|_| format!("invalid win length `{}`", win_length))?,
+            None => TicTacToe::get_default_win_length(size),
+        };
+        if !(3..=size).contains(&win_length) {
+            return Err(format!(
+                "invalid win length {} for a {}x{} board (expected 3 to {})",
+                win_length, size, size, size
+            ));
         }
 
-        // Check Diagonal Wins
-        if &board[0] != &Player::None && &board[0] == &board[4] && &board[0] == &board[8] {
-            return GameResult::Win(board[0].clone());
+        if rows.len() != size as usize {
+            return Err(format!(
+                "expected {} rows for a {}x{} board, got {}",
+                size,
+                size,
+                size,
+                rows.len()
+            ));
         }
+        let mut board = Vec::with_capacity((size * size) as usize);
+        for (row_index, row) in rows.iter().enumerate() {
+            if row.chars().count() != size as usize {
+                return Err(format!(
+                    "expected {} cells in row {}, got {}",
+                    size,
+                    row_index + 1,
+                    row.chars().count()
+                ));
+            }
+            for cell in row.chars() {
+                board.push(match cell.to_ascii_lowercase() {
+                    'x' => Cell::Mark(Player::X),
+                    'o' => Cell::Mark(Player::O),
+                    '.' => Cell::Empty,
+                    _ => {
+                        return Err(format!(
+                            "invalid cell `{}` (expected `x`, `o` or `.`)",
+                            cell
+                        ))
+                    }
+                });
+            }
+        }
+
+        let player = match player.to_ascii_lowercase().as_str() {
+            "x" => Player::X,
+            "o" => Player::O,
+            _ => {
+                return Err(format!(
+                    "invalid side to move `{}` (expected `x` or `o`)",
+                    player
+                ))
+            }
+        };
+
+        // Lines don't depend on the variant
+        TicTacToe::new(size, win_length, Variant::Standard).validate_position(&board, &player)?;
+
+        Ok(Position {
+            board,
+            player,
+            size,
+            win_length,
+        })
+    }
 
-        if &board[2] != &Player::None && &board[2] == &board[4] && &board[2] == &board[6] {
-            return GameResult::Win(board[2].clone());
+    /// rejects positions that can't be reached by alternating moves, whoever started
+    pub fn validate_position(&self, board: &Board, player: &Player) -> Result<(), String> {
+        let count = |player: &Player| {
+            board
+                .iter()
+                .filter(|cell| cell.is_marked_by(player))
+                .count()
+        };
+        // From the side to move round to the side that just moved, every side has as many
+        // marks as the one before it or one more
+        let counts: Vec<usize> = (0..Player::COUNT)
+            .map(|steps| count(&player.get_rotated(steps)))
+            .collect();
+        let (to_move, moved) = (counts[0], counts[Player::COUNT - 1]);
+        if counts.windows(2).any(|pair| pair[0] > pair[1]) || moved > to_move + 1 {
+            return Err(format!(
+                "impossible position, {:?} with {} marks can't be to move against {} marks",
+                player, to_move, moved
+            ));
         }
 
-        // Draw
-        if TicTacToe::is_full(&board) {
-            return GameResult::Draw;
+        let get_lines = |player: &Player| -> Vec<&Vec<usize>> {
+            self.lines
+                .iter()
+                .filter(|line| line.iter().all(|&index| board[index].is_marked_by(player)))
+                .collect()
+        };
+        if !get_lines(player).is_empty() {
+            return Err(format!(
+                "impossible position, {:?} completed a line but is to move",
+                player
+            ));
         }
 
-        GameResult::Playing
-    }
+        // The game ends at the first line, only the move that ended it can complete several
+        let last_player = player.get_rotated(Player::COUNT - 1);
+        if let Some((first, rest)) = get_lines(&last_player).split_first() {
+            if !first
+                .iter()
+                .any(|cell| rest.iter().all(|line| line.contains(cell)))
+            {
+                return Err(format!(
+                    "impossible position, {:?} completed lines no single move completes",
+                    last_player
+                ));
+            }
+        }
 
-    pub fn get_empty_board() -> Board {
-        let size = BOARD_SIZE * BOARD_SIZE;
-        (0..size).into_iter().map(|_| Player::None).collect()
+        Ok(())
     }
 
-    pub fn is_empty(board: &Board) -> bool {
-        let count: usize = board
+    /// cells that complete a line of `player` with the next move, losing in misère
+    pub fn get_winning_cells(&self, board: &Board, player: &Player) -> Vec<usize> {
+        let mut cells: Vec<usize> = self
+            .lines
             .iter()
-            .map(|x| x != &Player::None)
-            .filter(|x| x.clone())
-            .collect::<Vec<bool>>()
-            .len();
-        count == 0
+            .filter_map(|line| self.get_missing_cell(board, line, player))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
     }
 
-    pub fn is_full(board: &Board) -> bool {
-        let count: usize = board
-            .iter()
-            .map(|x| x == &Player::None)
-            .filter(|x| x.clone())
-            .collect::<Vec<bool>>()
-            .len();
-        count == 0
+    /// cells that leave `player` two different winning cells at once
+    pub fn get_fork_cells(&self, board: &Board, player: &Player) -> Vec<usize> {
+        let winning_cells = self.get_winning_cells(board, player);
+        TicTacToe::get_available_moves(board)
+            .into_iter()
+            .filter(|index| !winning_cells.contains(index))
+            .filter(|&index| {
+                let mut child = board.clone();
+                child[index] = Cell::Mark(player.clone());
+                self.get_winning_cells(&child, player).len() >= 2
+            })
+            .collect()
+    }
+
+    /// every cell `player` wins, has to block or forks at, the most urgent threat of a cell only.
+    /// In misère, every cell where `player` loses or the opponent would lose instead
+    pub fn get_threats(&self, board: &Board, player: &Player) -> Vec<(usize, Threat)> {
+        let mut threats: Vec<(usize, Threat)> = vec![];
+        let own_cells = self.get_winning_cells(board, player);
+        let opponent_cells = self.get_winning_cells(board, &player.get_next());
+        let cells = match self.variant {
+            Variant::Standard => vec![
+                (own_cells, Threat::Win),
+                (opponent_cells, Threat::Block),
+                (self.get_fork_cells(board, player), Threat::Fork),
+            ],
+            Variant::Misere => vec![(own_cells, Threat::Lose), (opponent_cells, Threat::Avoid)],
+        };
+        for (indexes, threat) in cells {
+            for index in indexes {
+                if !threats.iter().any(|(other_index, _)| *other_index == index) {
+                    threats.push((index, threat));
+                }
+            }
+        }
+        threats
+    }
+
+    /// most forcing tactical reason for `player` to play `index`
+    pub fn get_motif(&self, board: &Board, index: usize, player: &Player) -> Motif {
+        let mut child = board.clone();
+        child[index] = Cell::Mark(player.clone());
+        match self.get_game_result(&child) {
+            GameResult::Win(winner) if &winner == player => return Motif::Win,
+            GameResult::Win(_) => return Motif::Forced,
+            _ => {}
+        }
+
+        // Blocking and threatening lines only help when completing one wins
+        if self.variant == Variant::Standard {
+            let lines = self.lines.iter().filter(|line| line.contains(&index));
+            let opponent = player.get_next();
+            if let Some(line) = lines
+                .clone()
+                .find(|line| self.get_missing_cell(board, line, &opponent) == Some(index))
+            {
+                return Motif::Block(self.get_line_kind(line));
+            }
+
+            // Lines the move leaves one mark short, a fork threatens two different cells
+            let threats: Vec<(&Vec<usize>, usize)> = lines
+                .filter_map(|line| Some((line, self.get_missing_cell(&child, line, player)?)))
+                .collect();
+            if let Some((line, cell)) = threats.first() {
+                return if threats.iter().any(|(_, other_cell)| other_cell != cell) {
+                    Motif::Fork
+                } else {
+                    Motif::Threat(self.get_line_kind(line))
+                };
+            }
+        }
+
+        let size = self.size as usize;
+        let (row, col) = (index / size, index % size);
+        if size % 2 == 1 && row == size / 2 && col == size / 2 {
+            Motif::Centre
+        } else if (row == 0 || row == size - 1) && (col == 0 || col == size - 1) {
+            Motif::Corner
+        } else {
+            Motif::Other
+        }
+    }
+
+    /// the only empty cell of a line otherwise filled by `player`
+    fn get_missing_cell(&self, board: &Board, line: &[usize], player: &Player) -> Option<usize> {
+        let mut empty_cells = line.iter().filter(|&&index| board[index].is_empty());
+        let cell = *empty_cells.next()?;
+        let is_missing = empty_cells.next().is_none()
+            && line
+                .iter()
+                .all(|&index| index == cell || board[index].is_marked_by(player));
+        is_missing.then_some(cell)
+    }
+
+    fn get_line_kind(&self, line: &[usize]) -> LineKind {
+        match line[1] - line[0] {
+            1 => LineKind::Row,
+            step if step == self.size as usize => LineKind::Column,
+            _ => LineKind::Diagonal,
+        }
+    }
+
+    /// column letter and row number from the top left, e.g. `b3`
+    pub fn get_cell_name(&self, index: usize) -> String {
+        let size = self.size as usize;
+        format!(
+            "{}{}",
+            (b'a' + (index % size) as u8) as char,
+            index / size + 1
+        )
+    }
+
+    pub fn get_empty_board(&self) -> Board {
+        let size = self.size * self.size;
+        (0..size).map(|_| Cell::Empty).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_round_trip() {
+        for position in [
+            ".../.../... x 3 3",
+            "x../.o./... x 3 3",
+            "xo./.x./..o x 3 3",
+            "xxx/oxo/oox o 3 3",
+            "x..../.o.../..x../...o./..... x 5 4",
+        ] {
+            let parsed = TicTacToe::parse_position(position).unwrap();
+            let engine = TicTacToe::new(parsed.size, parsed.win_length, Variant::Standard);
+            assert_eq!(
+                engine.format_position(&parsed.board, &parsed.player),
+                position
+            );
+        }
+    }
+
+    #[test]
+    fn position_defaults_to_row_count() {
+        let parsed = TicTacToe::parse_position("X../.O./... x").unwrap();
+        assert_eq!((parsed.size, parsed.win_length), (3, 3));
+        assert_eq!(parsed.board[0], Cell::Mark(Player::X));
+        assert_eq!(parsed.board[4], Cell::Mark(Player::O));
+    }
+
+    #[test]
+    fn threads_find_the_same_scores() {
+        for (position, max_depth) in [
+            ("..../..../..../.... x 4 3", 5),
+            ("x.../.o../..x./.... o 4 4", 6),
+            ("...../..x../..o../...../..... x 5 4", 4),
+        ] {
+            let parsed = TicTacToe::parse_position(position).unwrap();
+            let mut engine = TicTacToe::new(parsed.size, parsed.win_length, Variant::Standard);
+            let mut single =
+                engine.search_move_scores(&parsed.board, parsed.player.clone(), max_depth);
+            engine.threads = 4;
+            let mut parallel =
+                engine.search_move_scores(&parsed.board, parsed.player.clone(), max_depth);
+            single.sort();
+            parallel.sort();
+            assert_eq!(single, parallel, "{}", position);
+        }
+    }
+
+    #[test]
+    fn motifs_follow_the_variant() {
+        let board = TicTacToe::parse_position("oo./x../... x 3 3")
+            .unwrap()
+            .board;
+        let standard = TicTacToe::new(3, 3, Variant::Standard);
+        let misere = TicTacToe::new(3, 3, Variant::Misere);
+        assert_eq!(
+            standard.get_motif(&board, 2, &Player::X),
+            Motif::Block(LineKind::Row)
+        );
+        assert_eq!(misere.get_motif(&board, 2, &Player::X), Motif::Corner);
+        assert_eq!(misere.get_motif(&board, 2, &Player::O), Motif::Forced);
+    }
+
+    #[test]
+    fn threats_follow_the_variant() {
+        let board = TicTacToe::parse_position("xx./oo./... x 3 3")
+            .unwrap()
+            .board;
+        let standard = TicTacToe::new(3, 3, Variant::Standard);
+        let misere = TicTacToe::new(3, 3, Variant::Misere);
+        assert_eq!(
+            standard.get_threats(&board, &Player::X),
+            vec![(2, Threat::Win), (5, Threat::Block)]
+        );
+        assert_eq!(
+            misere.get_threats(&board, &Player::X),
+            vec![(2, Threat::Lose), (5, Threat::Avoid)]
+        );
+    }
+
+    #[test]
+    fn position_errors() {
+        for (position, error) in [
+            ("x../.o./...", "expected the rows"),
+            ("x../.o./... x 3 3 3", "expected the rows"),
+            ("x../.o./... x three", "invalid size `three`"),
+            ("x../.o./... x 2", "invalid size 2"),
+            ("x../.o./... x 3 4", "invalid win length 4"),
+            ("x../.o./... x 3 long", "invalid win length `long`"),
+            ("x../.o. x 3 3", "expected 3 rows"),
+            ("x../.o../... x 3 3", "expected 3 cells in row 2"),
+            ("x../.a./... x 3 3", "invalid cell `a`"),
+            ("x../.o./... z 3 3", "invalid side to move `z`"),
+            ("xx./.o./... x 3 3", "impossible position"),
+            (
+                "xxx/oo./o.. x 3 3",
+                "impossible position, X completed a line",
+            ),
+            (
+                "xxx../...../xxx../o.o.o/.o.o. o 5 3",
+                "impossible position, X completed lines",
+            ),
+        ] {
+            let result = TicTacToe::parse_position(position);
+            assert!(
+                result
+                    .as_ref()
+                    .is_err_and(|message| message.starts_with(error)),
+                "{}: {:?}",
+                position,
+                result
+            );
+        }
     }
 }
@@ -0,0 +1,59 @@
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use crate::{
+    entities::{Board, Outcome, Threat},
+    theme::{Symbols, Theme},
+    widgets::cell_widget::CellWidget,
+};
+
+/// grid of cells, the area is split evenly between `size` rows and columns
+pub struct BoardWidget<'a> {
+    pub board: &'a Board,
+    pub size: u16,
+    pub selected_index: Option<usize>,
+    pub winning_line: Option<&'a [usize]>,
+    pub hint: Option<usize>,
+    pub move_outcomes: &'a [(usize, Outcome)],
+    pub threats: &'a [(usize, Threat)],
+    pub theme: &'a Theme,
+    pub symbols: &'a Symbols,
+}
+
+impl Widget for BoardWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let cell_width = area.width / self.size;
+        let cell_height = area.height / self.size;
+
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let index = ((row * self.size) + col) as usize;
+                let cell = CellWidget {
+                    cell: self.board[index].clone(),
+                    is_selected: self.selected_index == Some(index),
+                    is_winner: self.winning_line.is_some_and(|line| line.contains(&index)),
+                    is_hint: self.hint == Some(index),
+                    outcome: self
+                        .move_outcomes
+                        .iter()
+                        .find(|(move_index, _)| *move_index == index)
+                        .map(|(_, outcome)| *outcome),
+                    threat: self
+                        .threats
+                        .iter()
+                        .find(|(threat_index, _)| *threat_index == index)
+                        .map(|(_, threat)| *threat),
+                    theme: self.theme,
+                    symbols: self.symbols,
+                };
+
+                let cell_area = Rect::new(
+                    area.x + (col * cell_width),
+                    area.y + (row * cell_height),
+                    cell_width,
+                    cell_height,
+                );
+                cell.render(cell_area, buf);
+            }
+        }
+    }
+}
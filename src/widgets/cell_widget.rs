@@ -1,39 +1,104 @@
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Rect},
-    style::{Color, Style, Stylize},
+    style::{Style, Stylize},
     text::Text,
-    widgets::{Block, Padding, Widget},
+    widgets::{Block, BorderType, Padding, Widget},
 };
 
-use crate::{entities::Player, helpers::center};
+use crate::{
+    entities::{Cell, Outcome, Threat},
+    helpers::center,
+    theme::{Symbols, Theme},
+};
 
-pub struct CellWidget {
-    pub player: Player,
+pub struct CellWidget<'a> {
+    pub cell: Cell,
     pub is_selected: bool,
     pub is_winner: bool,
+    pub is_hint: bool,
+    /// heatmap value of an empty cell
+    pub outcome: Option<Outcome>,
+    /// shown instead of the heatmap value
+    pub threat: Option<Threat>,
+    pub theme: &'a Theme,
+    pub symbols: &'a Symbols,
 }
 
-impl Widget for CellWidget {
+impl Widget for CellWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        // Container
+        // Single row cells of big boards have no room for a border
+        if area.height < 3 {
+            self.render_compact(area, buf);
+            return;
+        }
+
+        // Container, the border type keeps selection & winner visible without colours
+        let (border_color, border_type) = match (self.is_selected, self.is_winner, self.is_hint) {
+            (true, _, _) => (self.theme.selection, BorderType::Thick),
+            (false, true, _) => (self.theme.winner, BorderType::Double),
+            (false, false, true) => (self.theme.hint, BorderType::Rounded),
+            _ => (self.theme.cell_border, BorderType::Plain),
+        };
         let block = Block::bordered()
-            .border_style(Style::default().fg(if self.is_selected {
-                Color::LightYellow
-            } else {
-                match (&self.player, self.is_winner) {
-                    (Player::X, true) | (Player::O, true) => self.player.get_color(),
-                    _ => Color::DarkGray,
-                }
-            }))
+            .border_style(Style::default().fg(border_color))
+            .border_type(border_type)
             .padding(Padding::ZERO);
 
         block.render(area, buf);
 
-        if self.player != Player::None {
-            let text = Text::raw(self.player.get_text()).fg(self.player.get_color());
+        if let Cell::Mark(player) = &self.cell {
+            let text = Text::raw(self.symbols.get_player_symbol(player))
+                .fg(self.theme.get_player_color(player));
             let center_area = center(area, Constraint::Length(1), Constraint::Length(1));
             text.render(center_area, buf);
+        } else if let Some(threat) = self.threat {
+            let label = threat.get_label();
+            let width = label.len() as u16;
+            let text = Text::raw(label).fg(self.theme.get_threat_color(&threat));
+            let center_area = center(area, Constraint::Length(width), Constraint::Length(1));
+            text.render(center_area, buf);
+        } else if let Some(outcome) = self.outcome {
+            let label = outcome.get_label();
+            let width = label.len() as u16;
+            let text = Text::raw(label).fg(self.theme.get_outcome_color(&outcome));
+            let center_area = center(area, Constraint::Length(width), Constraint::Length(1));
+            text.render(center_area, buf);
         }
     }
 }
+
+impl CellWidget<'_> {
+    fn render_compact(&self, area: Rect, buf: &mut Buffer) {
+        let (symbol, color) = match (&self.cell, self.threat, self.outcome) {
+            // First letter of the threat, the whole label doesn't fit
+            (Cell::Empty, Some(threat), _) => (
+                threat.get_label()[..1].to_string(),
+                self.theme.get_threat_color(&threat),
+            ),
+            (Cell::Empty, None, Some(outcome)) => {
+                (outcome.get_label(), self.theme.get_outcome_color(&outcome))
+            }
+            (Cell::Empty, None, None) => ("·".to_string(), self.theme.cell_border),
+            (Cell::Mark(player), _, _) => (
+                self.symbols.get_player_symbol(player).to_string(),
+                self.theme.get_player_color(player),
+            ),
+        };
+
+        let mut style = Style::default().fg(color);
+        if self.is_winner {
+            style = style.bg(self.theme.winner).bold();
+        }
+        if self.is_hint {
+            style = style.underlined();
+        }
+        if self.is_selected {
+            style = style.reversed();
+        }
+
+        let text = Text::styled(format!("{:^3}", symbol), style);
+        let center_area = center(area, Constraint::Length(3), Constraint::Length(1));
+        text.render(center_area, buf);
+    }
+}
@@ -1 +1,3 @@
+pub mod board_widget;
 pub mod cell_widget;
+pub mod tree_widget;
@@ -0,0 +1,74 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    explorer::TreeRow,
+    theme::{Symbols, Theme},
+    tictactoe::TicTacToe,
+};
+
+/// rows of a move tree, one move per line indented by its depth, scrolled to keep the selected
+/// row in view
+pub struct TreeWidget<'a> {
+    pub rows: &'a [TreeRow<'a>],
+    pub selected: &'a [usize],
+    pub engine: &'a TicTacToe,
+    pub theme: &'a Theme,
+    pub symbols: &'a Symbols,
+}
+
+impl Widget for TreeWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let height = area.height as usize;
+        let selected_row = self
+            .rows
+            .iter()
+            .position(|row| row.path == self.selected)
+            .unwrap_or(0);
+        // The selected row stays in the middle once there is more above it than half the area
+        let offset = selected_row
+            .saturating_sub(height / 2)
+            .min(self.rows.len().saturating_sub(height));
+
+        for (line, row) in self.rows.iter().skip(offset).take(height).enumerate() {
+            let node = row.node;
+            let marker = match &node.children {
+                Some(children) if children.is_empty() => " ",
+                _ if node.is_expanded => "▾",
+                _ => "▸",
+            };
+            let name = format!("{:<3}", self.engine.get_cell_name(node.index));
+            let name = if node.is_best {
+                name.fg(self.theme.hint).bold()
+            } else {
+                Span::from(name)
+            };
+
+            let mut line_spans = vec![
+                format!("{}{} ", "  ".repeat(row.path.len() - 1), marker).fg(self.theme.muted),
+                self.symbols
+                    .get_player_symbol(&node.player)
+                    .fg(self.theme.get_player_color(&node.player)),
+                " ".into(),
+                name,
+                " ".into(),
+                format!("{:<3}", node.outcome.get_label())
+                    .fg(self.theme.get_outcome_color(&node.outcome)),
+                format!(" {:+}", node.score).fg(self.theme.muted),
+            ];
+            let mut style = Style::default();
+            if row.path == self.selected {
+                style = style.reversed();
+                line_spans.push(" ".into());
+            }
+
+            let line_area = Rect::new(area.x, area.y + line as u16, area.width, 1);
+            Line::from(line_spans).style(style).render(line_area, buf);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+use crate::{
+    bitboard::{Bitboard, MAX_CELLS},
+    entities::Player,
+    symmetry::Symmetries,
+};
+
+/// random key of every cell for each player, the same on every run
+const KEYS: [[u64; MAX_CELLS]; Player::COUNT] = generate_keys();
+/// hashes of a board, one per orientation for each colouring
+const HASHES: usize = 8 * Player::COUNT;
+
+// SplitMix64, good enough for hashing and usable in a const fn
+const fn generate_keys() -> [[u64; MAX_CELLS]; Player::COUNT] {
+    let mut keys = [[0; MAX_CELLS]; Player::COUNT];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut index = 0;
+    while index < Player::COUNT * MAX_CELLS {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut key = state;
+        key = (key ^ (key >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        key = (key ^ (key >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[index / MAX_CELLS][index % MAX_CELLS] = key ^ (key >> 31);
+        index += 1;
+    }
+    keys
+}
+
+/// keys of every cell as seen in each orientation of the board, for every colouring
+#[derive(Debug, Clone)]
+pub struct ZobristKeys {
+    /// hash change of each orientation when each player marks a cell, by cell
+    keys: Vec<[[u64; HASHES]; Player::COUNT]>,
+}
+
+impl ZobristKeys {
+    pub fn new(symmetries: &Symmetries, size: u16) -> Self {
+        let keys = (0..(size * size) as usize)
+            .map(|index| {
+                let mut keys = [[0; HASHES]; Player::COUNT];
+                for (side, player_keys) in keys.iter_mut().enumerate() {
+                    // Colouring `steps` hashes each mark as the player moving that many turns later
+                    for steps in 0..Player::COUNT {
+                        let colour = (side + steps) % Player::COUNT;
+                        for (symmetry, cell) in symmetries.get_transformed_cells(index).enumerate()
+                        {
+                            player_keys[steps * 8 + symmetry] = KEYS[colour][cell];
+                        }
+                    }
+                }
+                keys
+            })
+            .collect();
+
+        ZobristKeys { keys }
+    }
+
+    fn get(&self, index: usize, player: &Player) -> &[u64; HASHES] {
+        &self.keys[index][player.get_index()]
+    }
+}
+
+/// bitboard searched by making and unmaking moves, with the Zobrist hash of each of its 8
+/// orientations, then of the same with the colours rotated by one turn, and so on
+#[derive(Debug, Clone)]
+pub struct HashedBoard {
+    pub bitboard: Bitboard,
+    hashes: [u64; HASHES],
+}
+
+impl HashedBoard {
+    pub fn new(keys: &ZobristKeys, bitboard: Bitboard) -> Self {
+        let mut board = HashedBoard {
+            bitboard: Bitboard::default(),
+            hashes: [0; HASHES],
+        };
+        for player in Player::ALL {
+            for index in bitboard.get_marks(&player).get_cells() {
+                board.make_move(keys, index, &player);
+            }
+        }
+        board
+    }
+
+    pub fn make_move(&mut self, keys: &ZobristKeys, index: usize, player: &Player) {
+        self.bitboard.set(index, player);
+        self.toggle(keys, index, player);
+    }
+
+    /// takes back a move made with `make_move`
+    pub fn unmake_move(&mut self, keys: &ZobristKeys, index: usize, player: &Player) {
+        self.bitboard.clear(index, player);
+        self.toggle(keys, index, player);
+    }
+
+    fn toggle(&mut self, keys: &ZobristKeys, index: usize, player: &Player) {
+        for (hash, key) in self.hashes.iter_mut().zip(keys.get(index, player)) {
+            *hash ^= key;
+        }
+    }
+
+    /// the same for every orientation of the position and for the position with the colours
+    /// rotated, as long as the side to move is rotated too
+    pub fn get_key(&self, player: &Player) -> u64 {
+        // The colouring where `player` is hashed as the first side
+        let steps = (Player::COUNT - player.get_index()) % Player::COUNT;
+        *self.hashes[steps * 8..(steps + 1) * 8]
+            .iter()
+            .min()
+            .unwrap()
+    }
+}
+
+/// Zobrist keys are already random, hashing them again would only slow tables down
+#[derive(Debug, Default)]
+pub struct KeyHasher(u64);
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, _: &[u8]) {
+        unreachable!("only `u64` keys are hashed");
+    }
+
+    fn write_u64(&mut self, key: u64) {
+        self.0 = key;
+    }
+}
+
+/// table indexed by Zobrist key
+pub type KeyTable<T> = HashMap<u64, T, BuildHasherDefault<KeyHasher>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::Board, tictactoe::TicTacToe};
+
+    #[test]
+    fn keys_follow_moves_orientations_and_colours() {
+        for position in [
+            "x../.o./... x 3 3",
+            "xo./.x./..o o 3 3",
+            "x.o./.x../o..x/.... o 4 4",
+            "xx../o.o./..x./o... x 4 3",
+        ] {
+            let parsed = TicTacToe::parse_position(position).unwrap();
+            let symmetries = Symmetries::new(parsed.size);
+            let keys = ZobristKeys::new(&symmetries, parsed.size);
+            let get_hashed = |board: &Board| HashedBoard::new(&keys, Bitboard::from_board(board));
+            let hashed = get_hashed(&parsed.board);
+            let key = hashed.get_key(&parsed.player);
+
+            for index in TicTacToe::get_available_moves(&parsed.board) {
+                let mut moved = hashed.clone();
+                moved.make_move(&keys, index, &parsed.player);
+                assert_ne!(moved.get_key(&parsed.player.get_next()), key);
+                moved.unmake_move(&keys, index, &parsed.player);
+                assert_eq!(moved.get_key(&parsed.player), key, "{}", position);
+            }
+
+            for symmetry in symmetries.get_all() {
+                for steps in 0..Player::COUNT {
+                    let board: Board = Symmetries::apply(symmetry, &parsed.board)
+                        .iter()
+                        .map(|cell| cell.get_rotated(steps))
+                        .collect();
+                    let player = parsed.player.get_rotated(steps);
+                    assert_eq!(get_hashed(&board).get_key(&player), key, "{}", position);
+                }
+            }
+        }
+    }
+}